@@ -0,0 +1,230 @@
+use crate::ballot::{BallotRecord, Decision};
+use colored::Colorize;
+use ethabi::Address;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{self, Formatter};
+
+/// The minimum number of ballots two validators must both have voted on to be put in a bloc.
+const MIN_CO_VOTED: usize = 3;
+/// The maximum width of the name column in the agreement table.
+const MAX_NAME_WIDTH: usize = 24;
+
+/// For each pair of validators, the number of ballots they both voted on, and how often they
+/// made the same choice.
+pub struct AgreementMatrix {
+    /// The voting keys of the validators, in the order of the matrix rows and columns.
+    voters: Vec<Address>,
+    /// The number of ballots that both validators voted on.
+    co_voted: Vec<Vec<usize>>,
+    /// The number of ballots where both validators made the same choice.
+    agreed: Vec<Vec<usize>>,
+    /// The minimum share of agreement for two validators to be considered part of a bloc.
+    threshold: f64,
+}
+
+impl AgreementMatrix {
+    /// Counts the agreements between the given voters in all ballots.
+    pub fn new(voters: Vec<Address>, ballots: &[BallotRecord], threshold: f64) -> AgreementMatrix {
+        let n = voters.len();
+        let index: HashMap<Address, usize> =
+            voters.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+        let mut co_voted = vec![vec![0; n]; n];
+        let mut agreed = vec![vec![0; n]; n];
+        for record in ballots {
+            let decisions: Vec<(usize, Decision)> = record
+                .votes
+                .iter()
+                .filter_map(|vote| index.get(&vote.voter).map(|&i| (i, vote.decision)))
+                .collect();
+            for &(i, d_i) in &decisions {
+                for &(j, d_j) in &decisions {
+                    co_voted[i][j] += 1;
+                    if d_i == d_j {
+                        agreed[i][j] += 1;
+                    }
+                }
+            }
+        }
+        AgreementMatrix {
+            voters,
+            co_voted,
+            agreed,
+            threshold,
+        }
+    }
+
+    /// Returns the share of co-voted ballots where validators `i` and `j` made the same choice,
+    /// or `None` if there are none.
+    pub fn share(&self, i: usize, j: usize) -> Option<f64> {
+        match self.co_voted[i][j] {
+            0 => None,
+            co_voted => Some(self.agreed[i][j] as f64 / co_voted as f64),
+        }
+    }
+
+    /// Returns groups of validators that are connected by pairs who agreed in at least the
+    /// threshold share of at least `MIN_CO_VOTED` ballots, largest first.
+    pub fn blocs(&self) -> Vec<Vec<usize>> {
+        let n = self.voters.len();
+        let mut bloc_of: Vec<usize> = (0..n).collect();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let agree = self.share(i, j).map_or(false, |s| s >= self.threshold);
+                if agree && self.co_voted[i][j] >= MIN_CO_VOTED {
+                    let (old, new) = (bloc_of[j], bloc_of[i]);
+                    for b in bloc_of.iter_mut().filter(|b| **b == old) {
+                        *b = new;
+                    }
+                }
+            }
+        }
+        let mut blocs: Vec<Vec<usize>> = (0..n)
+            .map(|b| (0..n).filter(|&i| bloc_of[i] == b).collect::<Vec<_>>())
+            .filter(|bloc| bloc.len() > 1)
+            .collect();
+        blocs.sort_by_key(|bloc| Reverse(bloc.len()));
+        blocs
+    }
+
+    /// Returns the pairs of validators who made the same choice on every one of at least
+    /// `MIN_CO_VOTED` ballots they both voted on.
+    pub fn unanimous_pairs(&self) -> Vec<(usize, usize)> {
+        let n = self.voters.len();
+        (0..n)
+            .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+            .filter(|&(i, j)| {
+                self.co_voted[i][j] >= MIN_CO_VOTED && self.agreed[i][j] == self.co_voted[i][j]
+            })
+            .collect()
+    }
+
+    /// Writes the matrix as a table with the agreement percentages, with the given names, followed
+    /// by the list of blocs.
+    pub fn fmt_table(&self, f: &mut Formatter, names: &[String]) -> fmt::Result {
+        writeln!(f, "\n{}", "Agreement".bold())?;
+        let n = self.voters.len();
+        let name_width = names
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_NAME_WIDTH);
+        let num_width = n.to_string().len();
+        write!(f, "{:1$}", "", num_width + 2 + name_width)?;
+        for j in 0..n {
+            write!(f, " {:>4}", j + 1)?;
+        }
+        writeln!(f)?;
+        for (i, name) in names.iter().enumerate() {
+            let name: String = name.chars().take(name_width).collect();
+            write!(f, "{:>2$}  {:3$}", i + 1, name, num_width, name_width)?;
+            for j in 0..n {
+                match self.share(i, j) {
+                    _ if i == j => write!(f, " {:>4}", "·")?,
+                    Some(share) => write!(f, " {:>4.0}", share * 100.0)?,
+                    None => write!(f, " {:>4}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        let blocs = self.blocs();
+        let threshold = self.threshold * 100.0;
+        if blocs.is_empty() {
+            writeln!(f, "\nNo blocs with at least {:.0}% agreement.", threshold)?;
+        } else {
+            let title = format!("Blocs with at least {:.0}% agreement", threshold);
+            writeln!(f, "\n{}", title.bold())?;
+            for bloc in blocs {
+                let members: Vec<&str> = bloc.iter().map(|&i| names[i].as_str()).collect();
+                writeln!(f, "  {}", members.join(", "))?;
+            }
+        }
+        let pairs = self.unanimous_pairs();
+        if !pairs.is_empty() {
+            writeln!(f, "\n{}", "Always voted the same".bold())?;
+            for (i, j) in pairs {
+                let co_voted = self.co_voted[i][j];
+                writeln!(f, "  {} and {} ({} ballots)", names[i], names[j], co_voted)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serializes the voting keys, the agreement shares and co-voted ballot counts as matrices, and
+/// the blocs as lists of voting keys.
+impl Serialize for AgreementMatrix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let n = self.voters.len();
+        let shares: Vec<Vec<Option<f64>>> = (0..n)
+            .map(|i| (0..n).map(|j| self.share(i, j)).collect())
+            .collect();
+        let blocs: Vec<Vec<&Address>> = self
+            .blocs()
+            .into_iter()
+            .map(|bloc| bloc.into_iter().map(|i| &self.voters[i]).collect())
+            .collect();
+        let mut state = serializer.serialize_struct("AgreementMatrix", 5)?;
+        state.serialize_field("voters", &self.voters)?;
+        state.serialize_field("threshold", &self.threshold)?;
+        state.serialize_field("shares", &shares)?;
+        state.serialize_field("co_voted", &self.co_voted)?;
+        state.serialize_field("blocs", &blocs)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AgreementMatrix;
+    use crate::ballot::tests::ballot;
+    use crate::ballot::{BallotRecord, Decision, QuorumState};
+    use ethabi::Address;
+
+    const ACCEPT: Decision = Decision::Accept;
+    const REJECT: Decision = Decision::Reject;
+
+    /// Returns the agreement between the voters with the given numbers as keys.
+    fn matrix(voters: u64, votes: &[&[(u64, Decision)]], threshold: f64) -> AgreementMatrix {
+        let ballots: Vec<BallotRecord> = votes
+            .iter()
+            .map(|decisions| ballot(decisions, 0, 1, QuorumState::Accepted))
+            .collect();
+        let voters = (1..=voters).map(Address::from).collect();
+        AgreementMatrix::new(voters, &ballots, threshold)
+    }
+
+    #[test]
+    fn transitive_bloc() {
+        // 1 and 3 mostly disagree, but each of them always agrees with 2.
+        let all = [(1, ACCEPT), (2, ACCEPT), (3, ACCEPT)];
+        let split = [(1, ACCEPT), (3, REJECT)];
+        let m = matrix(3, &[&all, &all, &all, &split, &split], 0.8);
+        assert_eq!(Some(0.6), m.share(0, 2));
+        assert_eq!(vec![vec![0, 1, 2]], m.blocs());
+        assert_eq!(vec![(0, 1), (1, 2)], m.unanimous_pairs());
+    }
+
+    #[test]
+    fn too_few_co_voted() {
+        let both = [(1, ACCEPT), (2, ACCEPT)];
+        let m = matrix(2, &[&both, &both], 0.8);
+        assert_eq!(Some(1.0), m.share(0, 1));
+        assert!(m.blocs().is_empty());
+        assert!(m.unanimous_pairs().is_empty());
+    }
+
+    #[test]
+    fn threshold() {
+        let agree = [(1, ACCEPT), (2, ACCEPT)];
+        let disagree = [(1, ACCEPT), (2, REJECT)];
+        let votes: [&[(u64, Decision)]; 5] = [&agree, &agree, &agree, &agree, &disagree];
+        assert_eq!(vec![vec![0, 1]], matrix(2, &votes, 0.8).blocs());
+        let m = matrix(2, &votes, 0.81);
+        assert!(m.blocs().is_empty());
+        assert!(m.unanimous_pairs().is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+use crate::keys::KeyType;
+use crate::util;
+use colored::Colorize;
+use ethabi::Address;
+use serde::{Serialize, Serializer};
+use serde_derive::Serialize;
+use std::fmt::{self, Display, Formatter};
+
+/// The type of a ballot. Ballots in the contracts other than `VotingToChangeKeys` have one type
+/// per contract.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum BallotType {
+    /// Adds a key.
+    KeyAdding,
+    /// Removes a key.
+    KeyRemoval,
+    /// Replaces a key with a new one.
+    KeySwap,
+    /// Changes the minimum number of votes a ballot needs.
+    MinThreshold,
+    /// Replaces the address of one of the contracts.
+    ProxyAddress,
+    /// Sends, burns or freezes the emission funds.
+    EmissionFunds,
+    /// A type that is unknown to this tool.
+    Other(u64),
+}
+
+impl BallotType {
+    /// Returns whether this is a ballot of the `VotingToChangeKeys` contract. Only these count
+    /// toward the validators' total participation.
+    pub fn is_about_keys(self) -> bool {
+        match self {
+            BallotType::MinThreshold | BallotType::ProxyAddress | BallotType::EmissionFunds => {
+                false
+            }
+            BallotType::KeyAdding
+            | BallotType::KeyRemoval
+            | BallotType::KeySwap
+            | BallotType::Other(_) => true,
+        }
+    }
+}
+
+impl From<u64> for BallotType {
+    fn from(n: u64) -> BallotType {
+        match n {
+            1 => BallotType::KeyAdding,
+            2 => BallotType::KeyRemoval,
+            3 => BallotType::KeySwap,
+            n => BallotType::Other(n),
+        }
+    }
+}
+
+impl Display for BallotType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            BallotType::KeyAdding => write!(f, "add"),
+            BallotType::KeyRemoval => write!(f, "remove"),
+            BallotType::KeySwap => write!(f, "swap"),
+            BallotType::MinThreshold => write!(f, "change threshold"),
+            BallotType::ProxyAddress => write!(f, "change proxy address"),
+            BallotType::EmissionFunds => write!(f, "manage emission funds"),
+            BallotType::Other(n) => write!(f, "type {}", n),
+        }
+    }
+}
+
+/// Serializes the ballot type as a string, e.g. `"add"`.
+impl Serialize for BallotType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A voter's choice on a ballot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Decision {
+    Accept,
+    Reject,
+    /// A choice that is unknown to this tool.
+    Other(u64),
+}
+
+impl From<u64> for Decision {
+    fn from(n: u64) -> Decision {
+        match n {
+            1 => Decision::Accept,
+            2 => Decision::Reject,
+            n => Decision::Other(n),
+        }
+    }
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Decision::Accept => write!(f, "accept"),
+            Decision::Reject => write!(f, "reject"),
+            Decision::Other(n) => write!(f, "choice {}", n),
+        }
+    }
+}
+
+/// Serializes the decision as a string, e.g. `"accept"`.
+impl Serialize for Decision {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The outcome of a ballot, as reported by the voting contract.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuorumState {
+    /// The ballot has not been finalized yet.
+    InProgress,
+    Accepted,
+    Rejected,
+    /// A state that is unknown to this tool.
+    Other(u64),
+}
+
+impl From<u64> for QuorumState {
+    fn from(n: u64) -> QuorumState {
+        match n {
+            1 => QuorumState::InProgress,
+            2 => QuorumState::Accepted,
+            3 => QuorumState::Rejected,
+            n => QuorumState::Other(n),
+        }
+    }
+}
+
+impl Display for QuorumState {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            QuorumState::InProgress => write!(f, "in progress"),
+            QuorumState::Accepted => write!(f, "accepted"),
+            QuorumState::Rejected => write!(f, "rejected"),
+            QuorumState::Other(n) => write!(f, "state {}", n),
+        }
+    }
+}
+
+/// Serializes the quorum state as a string, e.g. `"accepted"`.
+impl Serialize for QuorumState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A vote cast on a ballot.
+#[derive(Clone, Debug, Serialize)]
+pub struct Vote {
+    /// The voter's voting key.
+    pub voter: Address,
+    pub decision: Decision,
+    /// The time the vote was cast, as a Unix timestamp.
+    pub time: u64,
+    /// The number of seconds between the start of the ballot and the vote.
+    pub latency: u64,
+}
+
+/// The details of a ballot, as returned by the voting contract.
+#[derive(Clone, Debug, Serialize)]
+pub struct BallotInfo {
+    /// The ballot ID.
+    pub id: u64,
+    pub ballot_type: BallotType,
+    /// The voting key of the ballot's creator.
+    pub creator: Address,
+    /// The key that is added, removed, or that replaces an existing one.
+    pub affected_key: Address,
+    /// The type of the affected key, or `None` if it is invalid.
+    pub affected_key_type: Option<KeyType>,
+    /// The mining key of the validator whose key is changed.
+    pub mining_key: Address,
+    /// For a ballot that adds a new validator, the new voting key.
+    pub new_voting_key: Option<Address>,
+    /// For a ballot that adds a new validator, the new payout key.
+    pub new_payout_key: Option<Address>,
+    /// The name of the affected validator, if known.
+    pub validator_name: Option<String>,
+    /// For a ballot that is not about keys, the proposed change, e.g. `threshold 3`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposed_value: Option<String>,
+    /// The voting start time, as a Unix timestamp.
+    pub start_time: u64,
+    /// The voting end time, as a Unix timestamp.
+    pub end_time: u64,
+    pub memo: String,
+    /// The minimum number of votes required for the ballot to be accepted.
+    pub min_threshold: u64,
+    /// Whether the ballot is still open, or was accepted or rejected.
+    pub quorum_state: QuorumState,
+}
+
+impl BallotInfo {
+    /// Returns a short description of the ballot, e.g. `add voting key`.
+    pub fn description(&self) -> String {
+        match self.affected_key_type {
+            Some(key_type) => format!("{} {} key", self.ballot_type, key_type),
+            None => format!("{}", self.ballot_type),
+        }
+    }
+
+    /// Returns what the ballot is about in short: the proposed value, or the affected key.
+    pub fn subject(&self) -> String {
+        match self.proposed_value {
+            Some(ref value) => value.clone(),
+            None => format!("{:?}", self.affected_key),
+        }
+    }
+}
+
+impl Display for BallotInfo {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let title = format!("Ballot {}: {}", self.id, self.description());
+        match self.proposed_value {
+            Some(ref value) => write!(f, "{} {}", title.bold(), value)?,
+            None => write!(f, "{} {:?}", title.bold(), self.affected_key)?,
+        }
+        if let Some(ref name) = self.validator_name {
+            write!(f, " ({})", name)?;
+        } else if !self.mining_key.is_zero() && self.mining_key != self.affected_key {
+            write!(f, " (mining key {:?})", self.mining_key)?;
+        }
+        if let Some(ref key) = self.new_voting_key {
+            write!(f, "\n  New voting key: {:?}", key)?;
+        }
+        if let Some(ref key) = self.new_payout_key {
+            write!(f, "\n  New payout key: {:?}", key)?;
+        }
+        write!(
+            f,
+            "\n  Created by {:?}, voting from {} to {}, threshold {}, {}",
+            self.creator,
+            util::format_time(self.start_time),
+            util::format_time(self.end_time),
+            self.min_threshold,
+            self.quorum_state
+        )?;
+        if !self.memo.is_empty() {
+            write!(f, "\n  Memo: {}", self.memo)?;
+        }
+        Ok(())
+    }
+}
+
+/// A ballot, and the voters who were allowed to vote on it.
+#[derive(Clone, Debug, Serialize)]
+pub struct BallotRecord {
+    /// The number of the block in which the ballot was created.
+    pub block: u64,
+    /// The timestamp of the block in which the ballot was created.
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub info: BallotInfo,
+    /// The voting keys of everyone who was allowed to cast a vote.
+    pub voters: Vec<Address>,
+    /// The votes that were cast.
+    pub votes: Vec<Vote>,
+    /// The voting keys of everyone who was allowed to vote but didn't.
+    pub missed: Vec<Address>,
+    /// Whether enough votes were cast to reach the ballot's threshold.
+    pub reached_threshold: bool,
+    /// For a rejected ballot, the number of additional accepting votes it would have needed to
+    /// pass.
+    pub votes_short: Option<usize>,
+}
+
+impl BallotRecord {
+    /// Returns the number of votes with the given decision.
+    pub fn count(&self, decision: Decision) -> usize {
+        self.votes.iter().filter(|v| v.decision == decision).count()
+    }
+
+    /// Returns whether the voter with the given voting keys voted on this ballot, or `None` if it
+    /// doesn't count toward their total participation: if none of the keys was allowed to vote,
+    /// or the ballot is not about keys.
+    pub fn participation(&self, keys: &[Address]) -> Option<bool> {
+        if !self.info.ballot_type.is_about_keys() || !self.voters.iter().any(|v| keys.contains(v)) {
+            return None;
+        }
+        Some(self.votes.iter().any(|vote| keys.contains(&vote.voter)))
+    }
+
+    /// Sets `reached_threshold` and `votes_short` from the votes and the quorum state.
+    pub fn update_outcome(&mut self) {
+        self.reached_threshold = self.votes.len() as u64 >= self.info.min_threshold;
+        self.votes_short = self.shortfall();
+    }
+
+    /// For a rejected ballot, returns the number of additional accepting votes that would have
+    /// been needed for it to pass: enough to reach the threshold, and more accepting than
+    /// rejecting votes. Returns `None` if the ballot was not rejected.
+    fn shortfall(&self) -> Option<usize> {
+        if self.info.quorum_state != QuorumState::Rejected {
+            return None;
+        }
+        let below_threshold = (self.info.min_threshold as usize).saturating_sub(self.votes.len());
+        let accepts = self.count(Decision::Accept);
+        let below_majority = (self.count(Decision::Reject) + 1).saturating_sub(accepts);
+        Some(below_threshold.max(below_majority))
+    }
+
+    /// Returns whether the validators who abstained could have changed the result of a rejected
+    /// ballot by voting.
+    pub fn abstainers_decisive(&self) -> bool {
+        self.votes_short
+            .map_or(false, |short| short > 0 && short <= self.missed.len())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{BallotInfo, BallotRecord, BallotType, Decision, QuorumState, Vote};
+    use ethabi::Address;
+
+    /// Returns a ballot with the given decisions by the voters with the given numbers as keys,
+    /// and the given number of absent voters.
+    pub fn ballot(
+        decisions: &[(u64, Decision)],
+        absent: u64,
+        min_threshold: u64,
+        quorum_state: QuorumState,
+    ) -> BallotRecord {
+        let votes: Vec<Vote> = decisions
+            .iter()
+            .map(|&(i, decision)| Vote {
+                voter: Address::from(i),
+                decision,
+                time: 1_530_000_000 + i,
+                latency: i,
+            })
+            .collect();
+        let first_absent = decisions.iter().map(|&(i, _)| i).max().unwrap_or(0) + 1;
+        let missed: Vec<Address> = (first_absent..first_absent + absent)
+            .map(Address::from)
+            .collect();
+        let mut voters: Vec<Address> = votes.iter().map(|vote| vote.voter).collect();
+        voters.extend(missed.iter().cloned());
+        let mut record = BallotRecord {
+            block: 1,
+            timestamp: 1_530_000_000,
+            info: BallotInfo {
+                id: 1,
+                ballot_type: BallotType::KeyAdding,
+                creator: Address::from(1),
+                affected_key: Address::from(100),
+                affected_key_type: None,
+                mining_key: Address::from(100),
+                new_voting_key: None,
+                new_payout_key: None,
+                validator_name: None,
+                proposed_value: None,
+                start_time: 1_530_000_000,
+                end_time: 1_530_172_800,
+                memo: String::new(),
+                min_threshold,
+                quorum_state,
+            },
+            voters,
+            votes,
+            missed,
+            reached_threshold: false,
+            votes_short: None,
+        };
+        record.update_outcome();
+        record
+    }
+
+    /// Returns a finalized ballot with the given numbers of votes and absent voters.
+    fn record(
+        accepts: u64,
+        rejects: u64,
+        absent: u64,
+        min_threshold: u64,
+        quorum_state: QuorumState,
+    ) -> BallotRecord {
+        let decisions: Vec<(u64, Decision)> = (1..=accepts + rejects)
+            .map(|i| {
+                let decision = if i <= accepts {
+                    Decision::Accept
+                } else {
+                    Decision::Reject
+                };
+                (i, decision)
+            })
+            .collect();
+        ballot(&decisions, absent, min_threshold, quorum_state)
+    }
+
+    #[test]
+    fn below_threshold() {
+        let record = record(2, 0, 2, 3, QuorumState::Rejected);
+        assert!(!record.reached_threshold);
+        assert_eq!(Some(1), record.votes_short);
+        assert!(record.abstainers_decisive());
+    }
+
+    #[test]
+    fn tie() {
+        let record = record(2, 2, 1, 3, QuorumState::Rejected);
+        assert!(record.reached_threshold);
+        assert_eq!(Some(1), record.votes_short);
+        assert!(record.abstainers_decisive());
+    }
+
+    #[test]
+    fn accepted() {
+        let record = record(3, 0, 2, 3, QuorumState::Accepted);
+        assert_eq!(None, record.votes_short);
+        assert!(!record.abstainers_decisive());
+    }
+
+    #[test]
+    fn too_few_absentees() {
+        let record = record(0, 3, 2, 3, QuorumState::Rejected);
+        assert_eq!(Some(4), record.votes_short);
+        assert!(!record.abstainers_decisive());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::error::{Error, ErrorKind};
+use crate::util;
+use chrono::{DateTime, NaiveDate};
+use std::str::FromStr;
+
+/// The start or end of the range in which ballots are counted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bound {
+    /// A block number.
+    Block(u64),
+    /// A Unix timestamp.
+    Time(u64),
+}
+
+/// Parses a block number, an RFC 3339 date and time, e.g. `2018-04-01T12:00:00Z`, a date, e.g.
+/// `2018-04-01`, which means midnight UTC, or a duration before now, e.g. `30 days`.
+impl FromStr for Bound {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Bound, Error> {
+        let s = s.trim();
+        if let Ok(block) = s.parse() {
+            return Ok(Bound::Block(block));
+        }
+        if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Bound::Time(time.timestamp().max(0) as u64));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            let time = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+            return Ok(Bound::Time(time.and_utc().timestamp().max(0) as u64));
+        }
+        match parse_duration::parse(s) {
+            Ok(duration) => Ok(Bound::Time(
+                util::now_timestamp().saturating_sub(duration.as_secs()),
+            )),
+            Err(_) => Err(ErrorKind::InvalidBound(s.to_string()).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bound;
+    use crate::util;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Bound::Block(0), "0".parse().unwrap());
+        assert_eq!(Bound::Block(5_000_000), " 5000000 ".parse().unwrap());
+        // 2018-04-01 00:00:00 UTC
+        assert_eq!(Bound::Time(1_522_540_800), "2018-04-01".parse().unwrap());
+        assert_eq!(
+            Bound::Time(1_522_584_000),
+            "2018-04-01T12:00:00Z".parse().unwrap()
+        );
+        assert_eq!(
+            Bound::Time(1_522_576_800),
+            "2018-04-01T12:00:00+02:00".parse().unwrap()
+        );
+        assert!("2018-04-31".parse::<Bound>().is_err());
+        assert!("-1".parse::<Bound>().is_err());
+        assert!("yesterday-ish".parse::<Bound>().is_err());
+    }
+
+    #[test]
+    fn parse_duration() {
+        let before = util::now_timestamp();
+        let bound = "30 days".parse().unwrap();
+        let after = util::now_timestamp();
+        let month = 30 * 24 * 60 * 60;
+        match bound {
+            Bound::Time(time) => assert!(before - month <= time && time <= after - month),
+            Bound::Block(block) => panic!("parsed as block {}", block),
+        }
+    }
+}
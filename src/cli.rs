@@ -1,3 +1,6 @@
+use crate::config::{ColorChoice, Format};
+use crate::period::Bucket;
+use crate::table::SortKey;
 use clap::{App, Arg, ArgMatches};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -14,6 +17,7 @@ pub fn get_matches() -> ArgMatches<'static> {
             Arg::with_name("url")
                 .value_name("URL")
                 .help("The JSON-RPC endpoint")
+                .env("POA_BALLOT_STATS_URL")
                 .takes_value(true),
         )
         .arg(
@@ -23,11 +27,27 @@ pub fn get_matches() -> ArgMatches<'static> {
                 .help("More detailed output")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("details")
+                .short("d")
+                .long("details")
+                .help("List the full validator metadata below the table")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("contracts")
                 .short("c")
                 .long("contracts")
                 .help("JSON file with the contract addresses")
+                .env("POA_BALLOT_STATS_CONTRACTS")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("network")
+                .short("n")
+                .long("network")
+                .help("The network name, e.g. 'core' or 'sokol', if no contracts file is given")
+                .env("POA_BALLOT_STATS_NETWORK")
                 .takes_value(true),
         )
         .arg(
@@ -35,6 +55,7 @@ pub fn get_matches() -> ArgMatches<'static> {
                 .short("p")
                 .long("period")
                 .help("The period in which votes should be counted, e.g. '5 days', '2 months'.")
+                .env("POA_BALLOT_STATS_PERIOD")
                 .takes_value(true),
         )
         .arg(
@@ -42,6 +63,235 @@ pub fn get_matches() -> ArgMatches<'static> {
                 .short("b")
                 .long("block")
                 .help("The earliest block in which votes should be counted.")
+                .env("POA_BALLOT_STATS_BLOCK")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("since")
+                .long("since")
+                .value_name("BOUND")
+                .help(
+                    "Count ballots from this block number, date (e.g. '2018-04-01' or \
+                     '2018-04-01T12:00:00Z') or time ago (e.g. '30 days')",
+                )
+                .env("POA_BALLOT_STATS_SINCE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("until")
+                .long("until")
+                .value_name("BOUND")
+                .help("Count ballots up to this block number, or before this date or time ago")
+                .env("POA_BALLOT_STATS_UNTIL")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("windows")
+                .long("windows")
+                .value_name("LIST")
+                .help(
+                    "Compare the missed ballots in these windows before the end of the range, \
+                     e.g. '30d,90d,all', with trend arrows",
+                )
+                .env("POA_BALLOT_STATS_WINDOWS")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("expiry-window")
+                .long("expiry-window")
+                .value_name("PERIOD")
+                .help("Warn about licenses that expire within this period. Default: '30 days'")
+                .env("POA_BALLOT_STATS_EXPIRY_WINDOW")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stale-after")
+                .long("stale-after")
+                .value_name("PERIOD")
+                .help("Warn about metadata that hasn't changed for this period. Default: '1 year'")
+                .env("POA_BALLOT_STATS_STALE_AFTER")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("inactive-ballots")
+                .long("inactive-ballots")
+                .value_name("N")
+                .help("Flag validators who missed the last N ballots in a row. Default: 10")
+                .env("POA_BALLOT_STATS_INACTIVE_BALLOTS")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("inactive-after")
+                .long("inactive-after")
+                .value_name("PERIOD")
+                .help("Flag validators who haven't voted for this period. Default: '90 days'")
+                .env("POA_BALLOT_STATS_INACTIVE_AFTER")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .help("The output format.")
+                .env("POA_BALLOT_STATS_FORMAT")
+                .possible_values(Format::NAMES)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .short("s")
+                .long("sort")
+                .help("The order in which validators are listed. Default: 'missed'")
+                .env("POA_BALLOT_STATS_SORT")
+                .possible_values(SortKey::NAMES)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("half-life")
+                .long("half-life")
+                .value_name("DURATION")
+                .help(
+                    "Show a participation score where each ballot's weight halves with this age, \
+                     e.g. '6 months'",
+                )
+                .env("POA_BALLOT_STATS_HALF_LIFE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("min-ballots")
+                .long("min-ballots")
+                .value_name("N")
+                .help("Don't list validators who were eligible to vote on fewer ballots")
+                .env("POA_BALLOT_STATS_MIN_BALLOTS")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("min-sample")
+                .long("min-sample")
+                .value_name("N")
+                .help("Mark validators with fewer eligible ballots as unreliable. Default: 5")
+                .env("POA_BALLOT_STATS_MIN_SAMPLE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("only-missing")
+                .long("only-missing")
+                .help("Only list validators who missed at least one ballot")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("former")
+                .long("former")
+                .help("Also list former validators, whose mining key was removed")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .long("filter")
+                .value_name("TEXT")
+                .help("Only list validators whose name or keys contain this text")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .long("columns")
+                .value_name("LIST")
+                .help(
+                    "Comma-separated table columns: missed, percent, interval, score, \
+                     voting-key, mining-key, payout-key, voting-address, mining-address, \
+                     payout-address, name, median-latency, p90-latency, last-day, streak, \
+                     longest-streak, last-vote, added, removed, failed-absent, decisive-absent, \
+                     created, success, own-votes, open-ballots",
+                )
+                .env("POA_BALLOT_STATS_COLUMNS")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("bucket")
+                .long("bucket")
+                .value_name("PERIOD")
+                .help("Also list participation per week, month or quarter")
+                .env("POA_BALLOT_STATS_BUCKET")
+                .possible_values(Bucket::NAMES)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("by-type")
+                .long("by-type")
+                .help("Also list participation per ballot type and affected key type")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("agreement")
+                .long("agreement")
+                .help("List how often each pair of validators voted the same way, and find blocs")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("bloc-threshold")
+                .long("bloc-threshold")
+                .value_name("PERCENT")
+                .help("The minimum agreement for validators to form a bloc. Default: 90")
+                .env("POA_BALLOT_STATS_BLOC_THRESHOLD")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("quorum")
+                .long("quorum")
+                .help("List rejected ballots, and the absent validators who could have passed them")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("creators")
+                .long("creators")
+                .help("List the ballots each validator created, and how many were accepted")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("validator")
+                .long("validator")
+                .value_name("KEY_OR_NAME")
+                .help(
+                    "Print the full record of the validator with this voting or mining key or name",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("Whether to color the output. Default: 'auto'")
+                .env("POA_BALLOT_STATS_COLOR")
+                .possible_values(ColorChoice::NAMES)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("appendix")
+                .long("appendix")
+                .help("In markdown format, list all ballots and who missed them below the table")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("ballots-csv")
+                .long("ballots-csv")
+                .value_name("FILE")
+                .help("Write a CSV file with one row for each ballot and eligible voter")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("FILE")
+                .help("The configuration file, e.g. ~/.config/poa-ballot-stats/config.toml")
+                .env("POA_BALLOT_STATS_CONFIG")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .value_name("NAME")
+                .help("The profile in the configuration file to use. Default: 'default'")
+                .env("POA_BALLOT_STATS_PROFILE")
                 .takes_value(true),
         )
         .get_matches()
@@ -0,0 +1,212 @@
+use crate::error::{Error, ErrorKind};
+use crate::stats::ColorThresholds;
+use ethabi::Address;
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The name of the profile that is used if none is selected explicitly.
+const DEFAULT_PROFILE: &str = "default";
+
+/// The contents of a configuration file: a map of named profiles.
+///
+/// Example:
+///
+/// ```toml
+/// [profile.default]
+/// url = "https://core.poa.network"
+/// network = "core"
+/// period = "10 weeks"
+///
+/// [profile.sokol]
+/// url = "https://sokol.poa.network"
+/// network = "sokol"
+/// exclude = ["0x…"]
+/// colors = { red = 40.0, yellow = 20.0 }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    profile: BTreeMap<String, Profile>,
+}
+
+/// A named set of settings. Every setting can be overridden on the command line or by an
+/// environment variable.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The JSON-RPC endpoint.
+    pub url: Option<String>,
+    /// The network name, e.g. `core` or `sokol`, selecting the file in the `contracts` folder.
+    pub network: Option<String>,
+    /// The JSON file with the contract addresses. Takes precedence over `network`.
+    pub contracts: Option<String>,
+    /// The period in which votes should be counted, e.g. `5 days`, `2 months`.
+    pub period: Option<String>,
+    /// The earliest block in which votes should be counted.
+    pub block: Option<u64>,
+    /// The first block, date or time ago from which ballots are counted, e.g. `2018-04-01`.
+    pub since: Option<String>,
+    /// The last block, or the date or time ago before which ballots are counted.
+    pub until: Option<String>,
+    /// The comma-separated windows in which participation is compared, e.g. `30d,90d,all`.
+    pub windows: Option<String>,
+    /// The output format.
+    pub format: Option<String>,
+    /// Voting or mining keys of validators that should not be listed.
+    #[serde(default)]
+    pub exclude: Vec<Address>,
+    /// The missed ballot percentages at which lines are highlighted.
+    pub colors: Option<ColorThresholds>,
+    /// Warn about licenses that expire within this period, e.g. `30 days`.
+    pub expiry_window: Option<String>,
+    /// Warn about metadata that hasn't been updated for this period, e.g. `12 months`.
+    pub stale_after: Option<String>,
+    /// Flag validators who missed this many of the latest ballots in a row.
+    pub inactive_ballots: Option<usize>,
+    /// Flag validators who haven't voted for this period, e.g. `3 months`.
+    pub inactive_after: Option<String>,
+    /// The order in which validators are listed, e.g. `name`.
+    pub sort: Option<String>,
+    /// Validators with fewer eligible ballots are not listed.
+    pub min_ballots: Option<usize>,
+    /// The comma-separated table columns, e.g. `missed,percent,voting-address,name`.
+    pub columns: Option<String>,
+    /// Whether to color the output: `auto`, `always` or `never`.
+    pub color: Option<String>,
+    /// List participation per `week`, `month` or `quarter`.
+    pub bucket: Option<String>,
+    /// The minimum percentage of agreement for validators to be grouped into a bloc.
+    pub bloc_threshold: Option<f64>,
+    /// The half-life of the recency-weighted participation score, e.g. `6 months`.
+    pub half_life: Option<String>,
+    /// Validators with fewer eligible ballots are marked as having too few for a reliable share.
+    pub min_sample: Option<usize>,
+}
+
+impl Profile {
+    /// Returns the contract addresses file configured in this profile, if any.
+    pub fn contracts_file(&self) -> Option<String> {
+        self.contracts
+            .clone()
+            .or_else(|| self.network.as_ref().map(|network| network_file(network)))
+    }
+
+    /// Removes the range in which ballots are counted, so that it can be replaced as a whole.
+    pub fn clear_range(&mut self) {
+        self.period = None;
+        self.block = None;
+        self.since = None;
+        self.until = None;
+    }
+}
+
+/// The output format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// A colored table for the terminal.
+    Text,
+    /// A JSON document with all statistics and ballots.
+    Json,
+    /// Comma-separated values, with one row per validator.
+    Csv,
+    /// A GitHub-flavored markdown table.
+    Markdown,
+    /// A self-contained HTML page with charts.
+    Html,
+    /// The Prometheus text exposition format.
+    Prometheus,
+}
+
+impl Format {
+    /// The names of all supported formats.
+    pub const NAMES: &'static [&'static str] =
+        &["text", "json", "csv", "markdown", "html", "prometheus"];
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Format, Error> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            "prometheus" => Ok(Format::Prometheus),
+            _ => Err(ErrorKind::UnknownFormat(s.to_string()).into()),
+        }
+    }
+}
+
+/// When to color the text output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorChoice {
+    /// Only if the output is a terminal and the `NO_COLOR` environment variable is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// The names of all choices.
+    pub const NAMES: &'static [&'static str] = &["auto", "always", "never"];
+}
+
+impl FromStr for ColorChoice {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ColorChoice, Error> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(ErrorKind::UnknownColorChoice(s.to_string()).into()),
+        }
+    }
+}
+
+/// Returns the contract addresses file for the network with the given name.
+pub fn network_file(network: &str) -> String {
+    format!("contracts/{}.json", network)
+}
+
+/// Returns the default location of the configuration file,
+/// e.g. `~/.config/poa-ballot-stats/config.toml` on Linux.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("poa-ballot-stats").join("config.toml"))
+}
+
+/// Loads the profile with the given name from the configuration file.
+///
+/// If no file is specified, the default location is used, and it is not an error if it doesn't
+/// exist. If no profile is specified, the `default` profile is used, if there is one.
+pub fn load_profile(path: Option<&str>, name: Option<&str>) -> Result<Profile, Error> {
+    let config = match path.map(PathBuf::from) {
+        Some(path) => read_file(path)?,
+        None => match default_path() {
+            Some(ref path) if path.exists() => read_file(path.clone())?,
+            _ => ConfigFile::default(),
+        },
+    };
+    match name {
+        None => Ok(config
+            .profile
+            .get(DEFAULT_PROFILE)
+            .cloned()
+            .unwrap_or_default()),
+        Some(name) => match config.profile.get(name) {
+            Some(profile) => Ok(profile.clone()),
+            None => Err(ErrorKind::UnknownProfile(name.to_string()).into()),
+        },
+    }
+}
+
+fn read_file(path: PathBuf) -> Result<ConfigFile, Error> {
+    let contents = fs::read_to_string(&path)?;
+    Ok(toml::from_str(&contents)?)
+}
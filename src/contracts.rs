@@ -14,14 +14,37 @@ pub mod v2 {
     use_contract!(val_meta, "abi/v2/ValidatorMetadata.abi.json");
     use_contract!(voting, "abi/v2/VotingToChangeKeys.abi.json");
     use_contract!(consensus, "abi/v2/PoaNetworkConsensus.abi.json");
+    use_contract!(ballots_storage, "abi/v2/BallotsStorage.abi.json");
+    use_contract!(threshold, "abi/v2/VotingToChangeMinThreshold.abi.json");
+    use_contract!(proxy, "abi/v2/VotingToChangeProxyAddress.abi.json");
+    use_contract!(emission, "abi/v2/VotingToManageEmissionFunds.abi.json");
 }
 
 // The `use_contract!` macro triggers several Clippy warnings.
-#[allow(clippy::redundant_closure, clippy::needless_update)]
+#[allow(
+    clippy::too_many_arguments,
+    clippy::redundant_closure,
+    clippy::needless_update
+)]
 pub mod v1 {
     use ethabi_contract::use_contract;
 
     use_contract!(voting, "abi/v1/VotingToChangeKeys.abi.json");
+    use_contract!(threshold, "abi/v1/VotingToChangeMinThreshold.abi.json");
+    use_contract!(proxy, "abi/v1/VotingToChangeProxyAddress.abi.json");
+}
+
+/// A contract in which validators vote on ballots.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VotingContract {
+    /// `VotingToChangeKeys`, for adding, removing and swapping keys.
+    Keys,
+    /// `VotingToChangeMinThreshold`, for changing the number of votes a ballot needs.
+    MinThreshold,
+    /// `VotingToChangeProxyAddress`, for replacing one of the contracts.
+    Proxy,
+    /// `VotingToManageEmissionFunds`, for sending, burning or freezing the emission funds.
+    EmissionFunds,
 }
 
 #[derive(Deserialize)]
@@ -31,6 +54,33 @@ pub struct ContractV1V2Addresses {
     pub keys_manager_address: Address,
     pub voting_to_change_keys_address: Address,
     pub poa_address: Address,
+    /// The `BallotsStorage` contract, which is missing in custom files for older networks.
+    #[serde(default)]
+    pub ballots_storage_address: Option<Address>,
+    #[serde(default)]
+    pub voting_to_change_min_threshold_address: Option<Address>,
+    #[serde(default)]
+    pub voting_to_change_proxy_address: Option<Address>,
+    /// The emission funds contract, which only exists since the second hard fork.
+    #[serde(default)]
+    pub voting_to_manage_emission_funds_address: Option<Address>,
+}
+
+impl ContractV1V2Addresses {
+    /// Returns which voting contract has the given address, if any.
+    pub fn voting_contract(&self, addr: &Address) -> Option<VotingContract> {
+        if *addr == self.voting_to_change_keys_address {
+            Some(VotingContract::Keys)
+        } else if Some(*addr) == self.voting_to_change_min_threshold_address {
+            Some(VotingContract::MinThreshold)
+        } else if Some(*addr) == self.voting_to_change_proxy_address {
+            Some(VotingContract::Proxy)
+        } else if Some(*addr) == self.voting_to_manage_emission_funds_address {
+            Some(VotingContract::EmissionFunds)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -46,7 +96,6 @@ impl ContractAddresses {
     }
 
     pub fn is_voting(&self, addr: &Address) -> bool {
-        *addr == self.v1.voting_to_change_keys_address
-            || *addr == self.v2.voting_to_change_keys_address
+        self.v1.voting_contract(addr).is_some() || self.v2.voting_contract(addr).is_some()
     }
 }
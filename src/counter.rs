@@ -1,23 +1,44 @@
+use crate::ballot::{BallotInfo, BallotType, Decision, QuorumState, Vote};
+use crate::bound::Bound;
+use crate::contracts::v1::proxy::functions::voting_state as proxy_voting_state_v1_fn;
+use crate::contracts::v1::threshold::functions::voting_state as threshold_voting_state_v1_fn;
 use crate::contracts::v1::voting::events::{ballot_created as ballot_created_v1, vote as vote_v1};
+use crate::contracts::v1::voting::functions::voting_state as voting_state_v1_fn;
+use crate::contracts::v2::ballots_storage::functions::get_ballot_limit_per_validator as get_ballot_limit_fn;
 use crate::contracts::v2::consensus::functions::get_validators as get_validators_fn;
-use crate::contracts::v2::key_mgr::events::voting_key_changed;
+use crate::contracts::v2::emission::functions::get_ballot_info as emission_ballot_info_fn;
+use crate::contracts::v2::key_mgr::events::{
+    mining_key_changed, payout_key_changed, voting_key_changed,
+};
 use crate::contracts::v2::key_mgr::functions::{
     get_mining_key_by_voting as get_mining_key_by_voting_fn,
+    get_mining_key_history as get_mining_key_history_fn,
+    get_payout_by_mining as get_payout_by_mining_fn,
     get_voting_by_mining as get_voting_by_mining_fn,
 };
+use crate::contracts::v2::proxy::functions::get_ballot_info as proxy_ballot_info_fn;
+use crate::contracts::v2::threshold::functions::get_ballot_info as threshold_ballot_info_fn;
 use crate::contracts::v2::val_meta::functions::validators as validators_fn;
 use crate::contracts::v2::voting::events::{ballot_created, vote};
-use crate::contracts::ContractAddresses;
+use crate::contracts::v2::voting::functions::{
+    active_ballots_length as active_ballots_length_fn, get_ballot_info as get_ballot_info_fn,
+    get_min_threshold_of_voters as get_min_threshold_fn, get_quorum_state as get_quorum_state_fn,
+    validator_active_ballots as validator_active_ballots_fn,
+};
+use crate::contracts::v2::voting::logs::BallotCreated;
+use crate::contracts::{ContractAddresses, VotingContract};
 use crate::error::{Error, ErrorKind};
-use crate::stats::Stats;
+use crate::keys::{KeyChange, KeyType};
+use crate::stats::{BlockRange, Stats};
 use crate::util::{self, HexList, IntoBallot, TopicFilterExt, Web3LogExt};
+use crate::validator::Validator;
 use colored::{Color, Colorize};
 use ethabi::{Address, Bytes, FunctionOutputDecoder, Uint};
 use std::collections::BTreeSet;
 use std::default::Default;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use web3;
 use web3::futures::Future;
+use web3::types::BlockNumber;
 
 /// The maximum age in seconds of the latest block.
 const MAX_BLOCK_AGE: u64 = 60 * 60;
@@ -29,8 +50,10 @@ const ERR_BLOCK: &str = "failed to retrieve block";
 /// A vote counter, to read ballot statistics from the blockchain.
 pub struct Counter {
     verbose: bool,
-    start_time: SystemTime,
-    start_block: u64,
+    /// The bounds of the first block to be counted. The latest of them is used.
+    since: Vec<Bound>,
+    /// The bound of the last block to be counted.
+    until: Option<Bound>,
     addrs: ContractAddresses,
     web3: web3::Web3<web3::transports::Http>,
     _eloop: web3::transports::EventLoopHandle,
@@ -44,8 +67,8 @@ impl Counter {
 
         Counter {
             verbose: false,
-            start_time: UNIX_EPOCH,
-            start_block: 0,
+            since: Vec::new(),
+            until: None,
             addrs,
             web3,
             _eloop,
@@ -57,14 +80,16 @@ impl Counter {
         self.verbose = true;
     }
 
-    /// Sets the first block to be taken into account, by creation time.
-    pub fn set_start_time(&mut self, start_time: SystemTime) {
-        self.start_time = start_time;
+    /// Sets the first block to be taken into account, by number or creation time. If called
+    /// repeatedly, the latest of the given bounds is used.
+    pub fn set_since(&mut self, since: Bound) {
+        self.since.push(since);
     }
 
-    /// Sets the first block to be taken into account, by number.
-    pub fn set_start_block(&mut self, start_block: u64) {
-        self.start_block = start_block;
+    /// Sets the last block to be taken into account, by number or creation time. A time bound is
+    /// exclusive: only blocks created before that time are counted.
+    pub fn set_until(&mut self, until: Bound) {
+        self.until = Some(until);
     }
 
     /// Finds all logged ballots and returns statistics about how many were missed by each voter.
@@ -75,12 +100,19 @@ impl Counter {
         macro_rules! vprintln { ($($arg:tt)*) => { if self.verbose { println!($($arg)*); } } }
 
         // Find all ballots and voter changes. We don't filter by contract address, so we can make
-        // a single pass. Contract addresses are checked inside the loop.
+        // a single pass. Contract addresses are checked inside the loop. All voting contracts log
+        // the same `BallotCreated` and `Vote` events, so this also finds the ballots about the
+        // threshold, proxy addresses and emission funds.
+        let range = self.block_range()?;
+
         let ballot_or_change_filter = ballot_created::filter(None, None, None)
             .or(ballot_created_v1::filter(None, None, None))
-            .or(voting_key_changed::filter(None));
+            .or(voting_key_changed::filter(None))
+            .or(payout_key_changed::filter(None))
+            .or(mining_key_changed::filter());
 
         let mut voters: BTreeSet<Address> = BTreeSet::new();
+        let mut key_changes: Vec<KeyChange> = Vec::new();
         let mut stats = Stats::default();
 
         vprintln!("Collecting events…");
@@ -96,15 +128,55 @@ impl Counter {
                 event_found = true;
                 // If it is a `VotingKeyChanged`, update the current set of voters.
                 vprintln!("• {} {:?}", format!("#{}", block_num).bold(), change);
+                let timestamp = self.block_timestamp(BlockNumber::Number(block_num));
                 match change.action.as_str() {
                     "added" => {
                         voters.insert(change.key);
+                        stats.start_tenure(&change.key, timestamp);
                     }
                     "removed" => {
                         voters.remove(&change.key);
+                        // Ballots that were still open when the key was removed don't count.
+                        stats.remove_voting_key(&change.key, timestamp);
                     }
                     _ => vprintln!("  Unexpected key change action."),
                 }
+                key_changes.push(KeyChange {
+                    block: block_num,
+                    timestamp,
+                    key_type: KeyType::Voting,
+                    key: change.key,
+                    mining_key: change.mining_key,
+                    action: change.action,
+                });
+            } else if let Ok(change) = payout_key_changed::parse_log(log.clone().into_raw()) {
+                if !self.addrs.is_keys_manager(&log.address) {
+                    continue; // Event from another contract instance.
+                }
+                event_found = true;
+                vprintln!("• {} {:?}", format!("#{}", block_num).bold(), change);
+                key_changes.push(KeyChange {
+                    block: block_num,
+                    timestamp: self.block_timestamp(BlockNumber::Number(block_num)),
+                    key_type: KeyType::Payout,
+                    key: change.key,
+                    mining_key: change.mining_key,
+                    action: change.action,
+                });
+            } else if let Ok(change) = mining_key_changed::parse_log(log.clone().into_raw()) {
+                if !self.addrs.is_keys_manager(&log.address) {
+                    continue; // Event from another contract instance.
+                }
+                event_found = true;
+                vprintln!("• {} {:?}", format!("#{}", block_num).bold(), change);
+                key_changes.push(KeyChange {
+                    block: block_num,
+                    timestamp: self.block_timestamp(BlockNumber::Number(block_num)),
+                    key_type: KeyType::Mining,
+                    key: change.key,
+                    mining_key: change.key,
+                    action: change.action,
+                });
             } else if let Ok(ballot) =
                 ballot_created::parse_log(log.clone().into_raw()).or_else(|_| {
                     ballot_created_v1::parse_log(log.clone().into_raw()).map(IntoBallot::into)
@@ -114,19 +186,26 @@ impl Counter {
                     continue; // Event from another contract instance.
                 }
                 event_found = true;
-                if block_num < self.start_block || self.is_block_too_old(block_num) {
+                if block_num < range.from_block || block_num > range.to_block {
                     let num = format!("#{}", block_num);
-                    vprintln!("• {} Ballot too old; skipping: {:?}", num.bold(), ballot);
+                    vprintln!(
+                        "• {} Ballot out of range; skipping: {:?}",
+                        num.bold(),
+                        ballot
+                    );
                     continue;
                 }
+                let timestamp = self.block_timestamp(BlockNumber::Number(block_num));
                 // If it is a `BallotCreated`, find the corresponding votes and update the stats.
-                vprintln!("• {} {:?}", format!("#{}", block_num).bold(), ballot);
-                let voted = self.voters_for_ballot(ballot.id)?;
+                let info = self.ballot_info(log.address, &ballot, timestamp)?;
+                vprintln!("• {} {}", format!("#{}", block_num).bold(), info);
+                let votes = self.votes_for_ballot(log.address, ballot.id, info.start_time)?;
+                let voted: Vec<Address> = votes.iter().map(|vote| vote.voter).collect();
                 if self.verbose {
                     self.print_ballot_details(&voters, &voted);
                 }
                 voters.extend(voted.iter().cloned());
-                stats.add_ballot(&voters, &voted);
+                stats.add_ballot(block_num, timestamp, info, &voters, votes);
             } else {
                 return Err(ErrorKind::UnexpectedLogParams.into());
             }
@@ -136,6 +215,16 @@ impl Counter {
             return Err(ErrorKind::NoEventsFound.into());
         }
 
+        stats.set_range(range);
+        let voting_addr = self.addrs.v2.voting_to_change_keys_address;
+        let active_ballots = self.call_voting(voting_addr, active_ballots_length_fn::call())?;
+        stats.set_active_ballots(active_ballots.low_u64());
+        if let Some(storage_addr) = self.addrs.v2.ballots_storage_address {
+            let eth = self.web3.eth();
+            let limit = util::raw_call(storage_addr, &eth, get_ballot_limit_fn::call())?;
+            stats.set_ballot_limit(limit.low_u64());
+        }
+
         // Add all voters we haven't encountered so far.
         let mining_keys: Vec<Address> = self.call_poa(get_validators_fn::call())?;
         for mining_key in mining_keys {
@@ -150,6 +239,7 @@ impl Counter {
         vprintln!(""); // Add a new line between event log and table.
 
         // Finally, gather the metadata for all voters.
+        let mut current_mining_keys = BTreeSet::new();
         for voter in voters {
             let mining_key = match self.call_key_mgr(get_mining_key_by_voting_fn::call(voter)) {
                 Err(err) => {
@@ -164,6 +254,55 @@ impl Counter {
             }
             let validator = self.call_val_meta(validators_fn::call(mining_key))?.into();
             stats.set_metadata(&voter, mining_key, validator);
+            let open_ballots =
+                self.call_voting(voting_addr, validator_active_ballots_fn::call(mining_key))?;
+            stats.set_open_ballots(&voter, open_ballots.low_u64());
+            let payout_key = self.call_key_mgr(get_payout_by_mining_fn::call(mining_key))?;
+            let mining_keys = self.mining_key_history(mining_key)?;
+            let history = key_changes
+                .iter()
+                .filter(|change| mining_keys.contains(&change.mining_key))
+                .cloned()
+                .collect();
+            stats.set_keys(&voter, payout_key, history);
+            current_mining_keys.extend(mining_keys);
+        }
+
+        // The remaining voting keys were replaced, or belong to former validators. Each former
+        // validator is listed with their last voting key, and their metadata is found by the
+        // mining key it belonged to.
+        let former_voters: BTreeSet<Address> = stats.former_voters().into_iter().collect();
+        let mut former_mining_keys = BTreeSet::new();
+        for change in key_changes.iter().rev() {
+            if change.key_type != KeyType::Voting
+                || !former_voters.contains(&change.key)
+                || change.mining_key.is_zero()
+                || current_mining_keys.contains(&change.mining_key)
+                || !former_mining_keys.insert(change.mining_key)
+            {
+                continue;
+            }
+            let (voter, mining_key) = (change.key, change.mining_key);
+            let removed = key_changes
+                .iter()
+                .rev()
+                .find(|change| {
+                    change.key_type == KeyType::Mining
+                        && change.key == mining_key
+                        && change.action == "removed"
+                })
+                .or_else(|| Some(change).filter(|change| change.action == "removed"));
+            if let Some(removed) = removed {
+                stats.end_tenure(&voter, removed.timestamp);
+            }
+            let validator = self.call_val_meta(validators_fn::call(mining_key))?.into();
+            stats.set_metadata(&voter, mining_key, validator);
+            let history = key_changes
+                .iter()
+                .filter(|change| change.mining_key == mining_key)
+                .cloned()
+                .collect();
+            stats.set_keys(&voter, Address::zero(), history);
         }
         Ok(stats)
     }
@@ -191,6 +330,228 @@ impl Counter {
         }
     }
 
+    /// Returns the details of the given ballot from the voting contract with the given address.
+    /// The `timestamp` of the block in which it was created is used if the start time is unknown.
+    fn ballot_info(
+        &self,
+        voting_addr: Address,
+        ballot: &BallotCreated,
+        timestamp: u64,
+    ) -> Result<BallotInfo, Error> {
+        let id = ballot.id;
+        let mut info = BallotInfo {
+            id: id.low_u64(),
+            ballot_type: BallotType::from(ballot.ballot_type.low_u64()),
+            creator: ballot.creator,
+            affected_key: Address::zero(),
+            affected_key_type: None,
+            mining_key: Address::zero(),
+            new_voting_key: None,
+            new_payout_key: None,
+            validator_name: None,
+            proposed_value: None,
+            start_time: timestamp,
+            end_time: 0,
+            memo: String::new(),
+            min_threshold: 0,
+            quorum_state: QuorumState::InProgress,
+        };
+        let v1_contract = self.addrs.v1.voting_contract(&voting_addr);
+        let is_v1 = v1_contract.is_some();
+        let contract = v1_contract.or_else(|| self.addrs.v2.voting_contract(&voting_addr));
+        let contract = match contract {
+            Some(VotingContract::Keys) | None => {
+                self.key_ballot_info(voting_addr, is_v1, &mut info)?;
+                return Ok(info);
+            }
+            Some(contract) => contract,
+        };
+        info.ballot_type = match contract {
+            VotingContract::MinThreshold => BallotType::MinThreshold,
+            VotingContract::Proxy => BallotType::ProxyAddress,
+            _ => BallotType::EmissionFunds,
+        };
+        self.change_ballot_info(voting_addr, contract, is_v1, &mut info)?;
+        Ok(info)
+    }
+
+    /// Reads the details of a key ballot from the `VotingToChangeKeys` contract.
+    fn key_ballot_info(
+        &self,
+        voting_addr: Address,
+        is_v1: bool,
+        info: &mut BallotInfo,
+    ) -> Result<(), Error> {
+        let id = Uint::from(info.id);
+        let affected_key_type: Uint;
+        if is_v1 {
+            // The old contract returns all of the ballot's fields in a single call.
+            let (
+                start_time,
+                end_time,
+                affected_key,
+                key_type,
+                mining_key,
+                ..,
+                quorum_state,
+                _,
+                _,
+                min_threshold,
+                _,
+                memo,
+            ) = self.call_voting(voting_addr, voting_state_v1_fn::call(id))?;
+            info.affected_key = affected_key;
+            affected_key_type = key_type;
+            info.mining_key = mining_key;
+            info.start_time = start_time.low_u64();
+            info.end_time = end_time.low_u64();
+            info.memo = memo;
+            info.min_threshold = min_threshold.low_u64();
+            info.quorum_state = QuorumState::from(quorum_state.low_u64());
+        } else {
+            let (
+                start_time,
+                end_time,
+                affected_key,
+                key_type,
+                new_voting_key,
+                new_payout_key,
+                mining_key,
+                ..,
+                memo,
+                _,
+            ) = self.call_voting(voting_addr, get_ballot_info_fn::call(id))?;
+            info.affected_key = affected_key;
+            affected_key_type = key_type;
+            info.mining_key = mining_key;
+            info.new_voting_key = Some(new_voting_key).filter(|key| !key.is_zero());
+            info.new_payout_key = Some(new_payout_key).filter(|key| !key.is_zero());
+            info.start_time = start_time.low_u64();
+            info.end_time = end_time.low_u64();
+            info.memo = memo;
+            info.min_threshold = self
+                .call_voting(voting_addr, get_min_threshold_fn::call(id))?
+                .low_u64();
+            let quorum_state = self.call_voting(voting_addr, get_quorum_state_fn::call(id))?;
+            info.quorum_state = QuorumState::from(quorum_state.low_u64());
+        }
+        info.affected_key_type = KeyType::from_affected_key_type(affected_key_type.low_u64());
+        let name_key =
+            if info.mining_key.is_zero() && info.affected_key_type == Some(KeyType::Mining) {
+                info.affected_key
+            } else {
+                info.mining_key
+            };
+        info.validator_name = self.validator_name(name_key)?;
+        Ok(())
+    }
+
+    /// Reads the details of a ballot from one of the voting contracts that are not about keys.
+    fn change_ballot_info(
+        &self,
+        voting_addr: Address,
+        contract: VotingContract,
+        is_v1: bool,
+        info: &mut BallotInfo,
+    ) -> Result<(), Error> {
+        let id = Uint::from(info.id);
+        match (contract, is_v1) {
+            (VotingContract::MinThreshold, true) => {
+                let (start_time, end_time, .., quorum_state, _, min_threshold, value, _, memo) =
+                    self.call_voting(voting_addr, threshold_voting_state_v1_fn::call(id))?;
+                info.start_time = start_time.low_u64();
+                info.end_time = end_time.low_u64();
+                info.quorum_state = QuorumState::from(quorum_state.low_u64());
+                info.min_threshold = min_threshold.low_u64();
+                info.proposed_value = Some(format!("threshold {}", value));
+                info.memo = memo;
+            }
+            (VotingContract::Proxy, true) => {
+                let (
+                    start_time,
+                    end_time,
+                    ..,
+                    quorum_state,
+                    _,
+                    min_threshold,
+                    value,
+                    kind,
+                    _,
+                    memo,
+                ) = self.call_voting(voting_addr, proxy_voting_state_v1_fn::call(id))?;
+                info.start_time = start_time.low_u64();
+                info.end_time = end_time.low_u64();
+                info.quorum_state = QuorumState::from(quorum_state.low_u64());
+                info.min_threshold = min_threshold.low_u64();
+                info.proposed_value = Some(format!("contract type {}: {:?}", kind, value));
+                info.memo = memo;
+            }
+            (VotingContract::MinThreshold, false) => {
+                let (start_time, end_time, _, _, _, value, _, memo, _) =
+                    self.call_voting(voting_addr, threshold_ballot_info_fn::call(id))?;
+                info.start_time = start_time.low_u64();
+                info.end_time = end_time.low_u64();
+                info.proposed_value = Some(format!("threshold {}", value));
+                info.memo = memo;
+                self.change_ballot_outcome(voting_addr, info)?;
+            }
+            (VotingContract::Proxy, false) => {
+                let (start_time, end_time, _, _, _, value, kind, _, memo, _) =
+                    self.call_voting(voting_addr, proxy_ballot_info_fn::call(id))?;
+                info.start_time = start_time.low_u64();
+                info.end_time = end_time.low_u64();
+                info.proposed_value = Some(format!("contract type {}: {:?}", kind, value));
+                info.memo = memo;
+                self.change_ballot_outcome(voting_addr, info)?;
+            }
+            _ => {
+                let (start_time, end_time, _, _, _, memo, amount, .., receiver) =
+                    self.call_voting(voting_addr, emission_ballot_info_fn::call(id))?;
+                info.start_time = start_time.low_u64();
+                info.end_time = end_time.low_u64();
+                info.proposed_value = Some(format!("{} wei, receiver {:?}", amount, receiver));
+                info.memo = memo;
+                // After voting, the emission funds are sent, burned or frozen: there is no
+                // accepted or rejected state.
+                let state = self.call_voting(voting_addr, get_quorum_state_fn::call(id))?;
+                info.quorum_state = match state.low_u64() {
+                    1 => QuorumState::InProgress,
+                    state => QuorumState::Other(state),
+                };
+                info.min_threshold = self
+                    .call_voting(voting_addr, get_min_threshold_fn::call(id))?
+                    .low_u64();
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the quorum state and threshold of a ballot in one of the current voting contracts.
+    /// They all share the functions of the `VotingToChangeKeys` contract's base contract.
+    fn change_ballot_outcome(
+        &self,
+        voting_addr: Address,
+        info: &mut BallotInfo,
+    ) -> Result<(), Error> {
+        let id = Uint::from(info.id);
+        let quorum_state = self.call_voting(voting_addr, get_quorum_state_fn::call(id))?;
+        info.quorum_state = QuorumState::from(quorum_state.low_u64());
+        info.min_threshold = self
+            .call_voting(voting_addr, get_min_threshold_fn::call(id))?
+            .low_u64();
+        Ok(())
+    }
+
+    /// Returns the name of the validator with the given mining key, if known.
+    fn validator_name(&self, mining_key: Address) -> Result<Option<String>, Error> {
+        if mining_key.is_zero() {
+            return Ok(None);
+        }
+        let validator: Validator = self.call_val_meta(validators_fn::call(mining_key))?.into();
+        let name = validator.name().trim().to_string();
+        Ok(if name.is_empty() { None } else { Some(name) })
+    }
+
     /// Calls a function of the `ValidatorMetadata` contract and returns the decoded result.
     fn call_val_meta<D>(&self, fn_call: (Bytes, D)) -> Result<D::Output, web3::contract::Error>
     where
@@ -199,6 +560,19 @@ impl Counter {
         util::raw_call(self.addrs.v2.metadata_address, &self.web3.eth(), fn_call)
     }
 
+    /// Calls a function of the `VotingToChangeKeys` contract with the given address and returns
+    /// the decoded result.
+    fn call_voting<D>(
+        &self,
+        voting_addr: Address,
+        fn_call: (Bytes, D),
+    ) -> Result<D::Output, web3::contract::Error>
+    where
+        D: FunctionOutputDecoder,
+    {
+        util::raw_call(voting_addr, &self.web3.eth(), fn_call)
+    }
+
     /// Calls a function of the `KeysManager` contract and returns the decoded result.
     fn call_key_mgr<D>(&self, fn_call: (Bytes, D)) -> Result<D::Output, web3::contract::Error>
     where
@@ -219,44 +593,138 @@ impl Counter {
         util::raw_call(self.addrs.v2.poa_address, &self.web3.eth(), fn_call)
     }
 
-    fn voters_for_ballot(&self, id: Uint) -> Result<Vec<Address>, Error> {
+    /// Returns the given mining key, followed by all earlier mining keys of the same validator.
+    fn mining_key_history(&self, mining_key: Address) -> Result<Vec<Address>, Error> {
+        let mut keys = vec![mining_key];
+        loop {
+            let key = keys[keys.len() - 1];
+            let prev_key = self.call_key_mgr(get_mining_key_history_fn::call(key))?;
+            if prev_key.is_zero() || keys.contains(&prev_key) {
+                return Ok(keys);
+            }
+            keys.push(prev_key);
+        }
+    }
+
+    /// Returns all votes cast on the ballot with the given ID and start time in the voting
+    /// contract with the given address. Each contract numbers its ballots separately.
+    fn votes_for_ballot(
+        &self,
+        voting_addr: Address,
+        id: Uint,
+        start_time: u64,
+    ) -> Result<Vec<Vote>, Error> {
         let vote_filter = vote::filter(id, None).or(vote_v1::filter(id, None));
-        let is_voting = |log: &web3::types::Log| self.addrs.is_voting(&log.address);
+        let is_voting = |log: &web3::types::Log| log.address == voting_addr;
+        let to_vote = |voter, decision: Uint, time: Uint| Vote {
+            voter,
+            decision: Decision::from(decision.low_u64()),
+            time: time.low_u64(),
+            latency: time.low_u64().saturating_sub(start_time),
+        };
         vote_filter
             .logs(&self.web3)?
             .into_iter()
             .filter(is_voting)
             .map(|vote_log| {
                 vote::parse_log(vote_log.clone().into_raw())
-                    .map(|vote| vote.voter)
-                    .or_else(|_| vote_v1::parse_log(vote_log.into_raw()).map(|vote| vote.voter))
+                    .map(|vote| to_vote(vote.voter, vote.decision, vote.time))
+                    .or_else(|_| {
+                        vote_v1::parse_log(vote_log.into_raw())
+                            .map(|vote| to_vote(vote.voter, vote.decision, vote.time))
+                    })
                     .map_err(Error::from)
             })
             .collect()
     }
 
-    /// Returns `true` if the block with the given number is older than `start_time`.
-    fn is_block_too_old(&self, block_num: u64) -> bool {
-        self.is_block_older_than(
-            web3::types::BlockNumber::Number(block_num),
-            &self.start_time,
-        )
+    /// Returns the range of blocks in which ballots are counted, converting time bounds to block
+    /// numbers.
+    fn block_range(&self) -> Result<BlockRange, Error> {
+        let latest = self.web3.eth().block_number().wait()?.low_u64();
+        let timestamp = |number| self.block_timestamp(BlockNumber::Number(number));
+        let mut range = BlockRange {
+            from_block: 0,
+            to_block: latest,
+            from_time: None,
+            to_time: None,
+        };
+        for since in &self.since {
+            let from_block = match *since {
+                Bound::Block(block) => block,
+                Bound::Time(time) => {
+                    range.from_time = range.from_time.max(Some(time));
+                    first_block_at(time, latest, timestamp)
+                }
+            };
+            range.from_block = range.from_block.max(from_block);
+        }
+        let to_block = match self.until {
+            None => latest,
+            Some(Bound::Block(block)) => block,
+            Some(Bound::Time(time)) => {
+                range.to_time = Some(time);
+                first_block_at(time, latest, timestamp).saturating_sub(1)
+            }
+        };
+        range.to_block = to_block.min(latest);
+        Ok(range)
     }
 
     /// Shows a warning if the node's latest block is outdated.
     fn check_synced(&self) {
         let min_time = SystemTime::now() - Duration::from_secs(MAX_BLOCK_AGE);
-        if self.is_block_older_than(web3::types::BlockNumber::Latest, &min_time) {
+        if self.is_block_older_than(BlockNumber::Latest, &min_time) {
             eprintln!("WARNING: The node is not fully synchronized. Stats may be inaccurate.");
         }
     }
 
     /// Returns `true` if the block with the given number was created before the given time.
-    fn is_block_older_than(&self, number: web3::types::BlockNumber, time: &SystemTime) -> bool {
+    fn is_block_older_than(&self, number: BlockNumber, time: &SystemTime) -> bool {
+        let seconds = time.duration_since(UNIX_EPOCH).expect(ERR_EPOCH).as_secs();
+        self.block_timestamp(number) < seconds
+    }
+
+    /// Returns the timestamp of the block with the given number.
+    fn block_timestamp(&self, number: BlockNumber) -> u64 {
         let id = web3::types::BlockId::Number(number);
         let block_result = self.web3.eth().block(id).wait();
         let block = block_result.expect(ERR_BLOCK).expect(ERR_BLOCK);
-        let seconds = time.duration_since(UNIX_EPOCH).expect(ERR_EPOCH).as_secs();
-        block.timestamp < seconds.into()
+        block.timestamp.low_u64()
+    }
+}
+
+/// Returns the number of the first block created at or after the given time, or the block after
+/// `latest` if there is none, using a binary search over the block timestamps.
+fn first_block_at<F: Fn(u64) -> u64>(time: u64, latest: u64, block_timestamp: F) -> u64 {
+    let (mut low, mut high) = (0, latest + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if block_timestamp(mid) < time {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::first_block_at;
+
+    #[test]
+    fn first_block_at_time() {
+        // Blocks 0 to 4, created at 100, 110, 110, 120 and 130.
+        let timestamps = [100, 110, 110, 120, 130];
+        let find = |time| first_block_at(time, 4, |n| timestamps[n as usize]);
+        assert_eq!(0, find(0));
+        assert_eq!(0, find(100));
+        assert_eq!(1, find(101));
+        assert_eq!(1, find(110));
+        assert_eq!(3, find(111));
+        assert_eq!(4, find(130));
+        // There is no block at or after 131: the result is the block after the latest one.
+        assert_eq!(5, find(131));
     }
 }
@@ -0,0 +1,215 @@
+use crate::stats::Stats;
+use crate::util;
+use std::io::{self, Write};
+
+/// Writes a row of comma-separated values, quoting fields where necessary.
+fn write_row<W: Write, S: AsRef<str>>(writer: &mut W, fields: &[S]) -> io::Result<()> {
+    for (i, field) in fields.iter().map(AsRef::as_ref).enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        if field.contains(&[',', '"', '\n', '\r'][..]) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(writer, "{}", field)?;
+        }
+    }
+    write!(writer, "\r\n")
+}
+
+/// Formats a Unix timestamp as a date, or returns an empty string if it is zero.
+fn date(timestamp: u64) -> String {
+    if timestamp == 0 {
+        String::new()
+    } else {
+        util::format_date(timestamp)
+    }
+}
+
+/// Writes one row per listed validator, with their vote counts, keys and metadata.
+pub fn write_validators<W: Write>(mut writer: W, stats: &Stats) -> io::Result<()> {
+    let header = [
+        "voting_key",
+        "mining_key",
+        "payout_key",
+        "name",
+        "ballots",
+        "voted",
+        "missed",
+        "missed_percent",
+        "missed_lower",
+        "missed_upper",
+        "score",
+        "median_latency",
+        "p90_latency",
+        "last_day_share",
+        "current_streak",
+        "longest_streak",
+        "last_vote",
+        "added",
+        "removed",
+        "failed_absent",
+        "decisive_absent",
+        "created",
+        "accepted",
+        "rejected",
+        "voted_own",
+        "open_ballots",
+        "license_id",
+        "full_address",
+        "state",
+        "zip_code",
+        "expiration_date",
+        "created_date",
+        "updated_date",
+        "min_threshold",
+    ];
+    let half_life = stats.display_options().half_life;
+    let now = util::now_timestamp();
+    let labels = stats.period_labels();
+    let window_labels = stats.window_labels();
+    let categories = if stats.display_options().by_type {
+        stats.categories()
+    } else {
+        Vec::new()
+    };
+    let mut header: Vec<String> = header.iter().map(|name| name.to_string()).collect();
+    for label in &labels {
+        header.push(format!("ballots_{}", label));
+        header.push(format!("voted_{}", label));
+    }
+    for label in &window_labels {
+        header.push(format!("ballots_window_{}", label));
+        header.push(format!("voted_window_{}", label));
+    }
+    for category in &categories {
+        let category = category.replace(' ', "_");
+        header.push(format!("ballots_{}", category));
+        header.push(format!("voted_{}", category));
+    }
+    write_row(&mut writer, &header)?;
+    for (voting_key, vs) in stats.listed() {
+        let interval = vs.missed_interval();
+        let mut row = vec![
+            format!("{:?}", voting_key),
+            vs.mining_key
+                .map_or_else(String::new, |key| format!("{:?}", key)),
+            vs.payout_key
+                .map_or_else(String::new, |key| format!("{:?}", key)),
+            vs.name(),
+            vs.ballots.to_string(),
+            vs.voted.to_string(),
+            vs.missed().to_string(),
+            vs.missed_percent()
+                .map_or_else(String::new, |percent| format!("{:.1}", percent)),
+            interval.map_or_else(String::new, |i| format!("{:.1}", i.lower * 100.0)),
+            interval.map_or_else(String::new, |i| format!("{:.1}", i.upper * 100.0)),
+            half_life
+                .and_then(|half_life| vs.score(now, half_life))
+                .map_or_else(String::new, |score| format!("{:.1}", score)),
+        ];
+        match vs.latency() {
+            Some(latency) => row.extend(vec![
+                latency.median.to_string(),
+                latency.p90.to_string(),
+                format!("{:.3}", latency.last_day_share),
+            ]),
+            None => row.extend(vec![String::new(), String::new(), String::new()]),
+        }
+        row.extend(vec![
+            vs.current_streak.to_string(),
+            vs.longest_streak.to_string(),
+            vs.last_vote.map_or_else(String::new, date),
+            vs.tenure.added.map_or_else(String::new, date),
+            vs.tenure.removed.map_or_else(String::new, date),
+            vs.failed_absent.to_string(),
+            vs.decisive_absent.to_string(),
+            vs.created.total().to_string(),
+            vs.created.accepted.to_string(),
+            vs.created.rejected.to_string(),
+            vs.created.voted_own.to_string(),
+            vs.created
+                .open
+                .map_or_else(String::new, |open| open.to_string()),
+        ]);
+        match vs.validator {
+            Some(ref v) => row.extend(vec![
+                v.license_id.clone(),
+                v.full_address.clone(),
+                v.state.clone(),
+                v.zip_code.clone(),
+                date(v.expiration_date),
+                date(v.created_date),
+                date(v.updated_date),
+                v.min_threshold.to_string(),
+            ]),
+            // Keep the later columns in place if the metadata is missing.
+            None => row.extend(vec![String::new(); 8]),
+        }
+        let periods = stats.periods(voting_key).unwrap_or_default();
+        for label in &labels {
+            let ps = periods.get(label).cloned().unwrap_or_default();
+            row.push(ps.ballots.to_string());
+            row.push(ps.voted.to_string());
+        }
+        let windows = stats.windows(voting_key).unwrap_or_default();
+        for label in &window_labels {
+            let pw = windows.get(label).cloned().unwrap_or_default();
+            row.push(pw.ballots.to_string());
+            row.push(pw.voted.to_string());
+        }
+        for category in &categories {
+            let p = vs.by_type.get(category).cloned().unwrap_or_default();
+            row.push(p.ballots.to_string());
+            row.push(p.voted.to_string());
+        }
+        write_row(&mut writer, &row)?;
+    }
+    Ok(())
+}
+
+/// Writes one row for each ballot and each voter who was allowed to vote on it, with the voter's
+/// decision, if they voted.
+pub fn write_ballots<W: Write>(mut writer: W, stats: &Stats) -> io::Result<()> {
+    let header = [
+        "ballot_id",
+        "block",
+        "ballot_type",
+        "affected_key",
+        "affected_validator",
+        "start_time",
+        "end_time",
+        "quorum_state",
+        "voting_key",
+        "voter_name",
+        "voted",
+        "decision",
+        "vote_time",
+        "latency",
+    ];
+    write_row(&mut writer, &header)?;
+    for record in stats.ballots() {
+        let info = &record.info;
+        for voter in &record.voters {
+            let vote = record.votes.iter().find(|vote| vote.voter == *voter);
+            let row = vec![
+                info.id.to_string(),
+                record.block.to_string(),
+                info.description(),
+                info.subject(),
+                info.validator_name.clone().unwrap_or_default(),
+                util::format_time(info.start_time),
+                util::format_time(info.end_time),
+                info.quorum_state.to_string(),
+                format!("{:?}", voter),
+                stats.get(voter).map_or_else(String::new, |vs| vs.name()),
+                vote.is_some().to_string(),
+                vote.map_or_else(String::new, |vote| vote.decision.to_string()),
+                vote.map_or_else(String::new, |vote| util::format_time(vote.time)),
+                vote.map_or_else(String::new, |vote| vote.latency.to_string()),
+            ];
+            write_row(&mut writer, &row)?;
+        }
+    }
+    Ok(())
+}
@@ -1,15 +1,12 @@
-use error_chain::{
-    error_chain, error_chain_processing, impl_error_chain_kind, impl_error_chain_processed,
-    impl_extract_backtrace,
-};
-use ethabi;
-use web3;
+use error_chain::error_chain;
 
 error_chain! {
     foreign_links {
         Ethabi(ethabi::Error);
         Web3(web3::Error);
         Contract(web3::contract::Error);
+        Io(std::io::Error);
+        Toml(toml::de::Error);
     }
 
     errors {
@@ -20,5 +17,45 @@ error_chain! {
             description("No events found. \
                          Make sure your node is running in 'full' mode, not 'light'."),
         }
+        UnknownProfile(name: String) {
+            description("Unknown profile"),
+            display("Profile '{}' not found in the configuration file", name),
+        }
+        UnknownFormat(name: String) {
+            description("Unknown output format"),
+            display("Unknown output format '{}'", name),
+        }
+        UnknownSortKey(name: String) {
+            description("Unknown sort key"),
+            display("Unknown sort key '{}'", name),
+        }
+        UnknownColumn(name: String) {
+            description("Unknown column"),
+            display("Unknown column '{}'", name),
+        }
+        ValidatorNotFound(query: String) {
+            description("Validator not found"),
+            display("No validator with the key or name '{}' found", query),
+        }
+        AmbiguousValidator(query: String, names: String) {
+            description("Ambiguous validator"),
+            display("Several validators match '{}': {}", query, names),
+        }
+        UnknownBucket(name: String) {
+            description("Unknown bucket length"),
+            display("Unknown bucket length '{}'", name),
+        }
+        UnknownColorChoice(name: String) {
+            description("Unknown color choice"),
+            display("Unknown color choice '{}'", name),
+        }
+        InvalidWindow(window: String) {
+            description("Invalid window"),
+            display("'{}' is neither 'all' nor a duration", window),
+        }
+        InvalidBound(bound: String) {
+            description("Invalid start or end of the range"),
+            display("'{}' is neither a block number, a date nor a duration", bound),
+        }
     }
 }
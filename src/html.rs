@@ -0,0 +1,223 @@
+use crate::ballot::BallotRecord;
+use crate::stats::Stats;
+use crate::util;
+use ethabi::Address;
+use std::io::{self, Write};
+
+/// The maximum number of ballots shown in a validator's participation sparkline.
+const SPARKLINE_BALLOTS: usize = 100;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+th { background: #eee; }
+table.sortable th { cursor: pointer; }
+tbody tr:nth-child(even) { background: #f7f7f7; }
+td.num { text-align: right; }
+.key { font-family: monospace; }
+.red { color: #c00; }
+.yellow { color: #b80; }
+.green { color: #080; }
+svg.sparkline rect.voted { fill: #3a3; }
+svg.sparkline rect.missed { fill: #d33; }
+svg.turnout rect { fill: #48c; }
+svg.turnout line { stroke: #d33; stroke-width: 0.5; }
+";
+
+const SCRIPT: &str = "
+document.querySelectorAll('table.sortable th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var tbody = th.closest('table').tBodies[0];
+    var col = th.cellIndex;
+    var asc = th.dataset.order !== 'asc';
+    th.dataset.order = asc ? 'asc' : 'desc';
+    var rows = Array.prototype.slice.call(tbody.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[col].dataset.sort || a.cells[col].textContent;
+      var y = b.cells[col].dataset.sort || b.cells[col].textContent;
+      var n = parseFloat(x) - parseFloat(y);
+      var c = isNaN(n) ? x.localeCompare(y) : n;
+      return asc ? c : -c;
+    });
+    rows.forEach(function (row) { tbody.appendChild(row); });
+  });
+});
+";
+
+/// Escapes the characters that have a special meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the voter's name, or their voting key if unknown.
+fn voter_name(stats: &Stats, voter: &Address) -> String {
+    match stats.get(voter).map(|vs| vs.name()) {
+        Some(ref name) if !name.is_empty() => escape(name),
+        _ => format!("<span class=\"key\">{:?}</span>", voter),
+    }
+}
+
+/// Returns an SVG sparkline with one bar for each of the most recent ballots of the voter with the
+/// given voting keys: green if they voted, red if they missed it.
+fn sparkline(ballots: &[BallotRecord], keys: &[Address]) -> String {
+    let eligible: Vec<(&BallotRecord, bool)> = ballots
+        .iter()
+        .filter_map(|record| record.participation(keys).map(|voted| (record, voted)))
+        .collect();
+    let recent = &eligible[eligible.len().saturating_sub(SPARKLINE_BALLOTS)..];
+    let mut svg = format!(
+        "<svg class=\"sparkline\" width=\"{}\" height=\"12\">",
+        recent.len() * 3
+    );
+    for (i, &(record, voted)) in recent.iter().enumerate() {
+        svg.push_str(&format!(
+            "<rect class=\"{}\" x=\"{}\" y=\"0\" width=\"2\" height=\"12\">\
+             <title>Ballot {}</title></rect>",
+            if voted { "voted" } else { "missed" },
+            i * 3,
+            record.info.id
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Returns an SVG bar chart with the share of eligible voters who voted on each ballot, and a
+/// marker for the ballot's threshold.
+fn turnout_chart(ballots: &[BallotRecord]) -> String {
+    let width = ballots.len().max(1) * 4;
+    let mut svg = format!(
+        "<svg class=\"turnout\" viewBox=\"0 0 {} 100\" preserveAspectRatio=\"none\" \
+         width=\"100%\" height=\"150\">",
+        width
+    );
+    for (i, record) in ballots.iter().enumerate() {
+        let eligible = record.voters.len().max(1);
+        let height = record.votes.len() * 100 / eligible;
+        let threshold = (record.info.min_threshold as usize * 100 / eligible).min(100);
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"3\" height=\"{}\">\
+             <title>Ballot {}: {} of {} voted</title></rect>",
+            i * 4,
+            100 - height,
+            height,
+            record.info.id,
+            record.votes.len(),
+            record.voters.len()
+        ));
+        svg.push_str(&format!(
+            "<line x1=\"{}\" x2=\"{}\" y1=\"{}\" y2=\"{}\"/>",
+            i * 4,
+            i * 4 + 3,
+            100 - threshold,
+            100 - threshold
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Writes the statistics as a self-contained HTML page, with a sortable table of validators,
+/// participation sparklines, a turnout chart and the list of ballots.
+pub fn write<W: Write>(mut w: W, stats: &Stats) -> io::Result<()> {
+    let colors = stats.display_options().colors;
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>POA ballot statistics</title>")?;
+    writeln!(w, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(w, "<h1>POA ballot statistics</h1>")?;
+    writeln!(w, "<p>{}</p>", escape(&stats.range().description()))?;
+
+    writeln!(w, "<h2>Validators</h2>")?;
+    writeln!(w, "<table class=\"sortable\">\n<thead><tr>")?;
+    writeln!(
+        w,
+        "<th>Missed</th><th>Missed %</th><th>Voting key</th><th>Mining key</th>"
+    )?;
+    writeln!(w, "<th>Payout key</th><th>Name</th><th>Recent ballots</th>")?;
+    writeln!(w, "</tr></thead>\n<tbody>")?;
+    for (voting_key, vs) in stats.listed() {
+        let class = match vs.missed_percent() {
+            Some(percent) if percent >= f64::from(colors.red) => "red",
+            Some(percent) if percent >= f64::from(colors.yellow) => "yellow",
+            Some(_) => "green",
+            None => "",
+        };
+        let key = |key: Option<Address>| key.map_or_else(String::new, |k| format!("{:?}", k));
+        writeln!(
+            w,
+            "<tr class=\"{}\"><td class=\"num\" data-sort=\"{}\">{}/{}</td>\
+             <td class=\"num\">{}</td><td class=\"key\">{}</td><td class=\"key\">{}</td>\
+             <td class=\"key\">{}</td><td>{}</td><td>{}</td></tr>",
+            class,
+            vs.missed(),
+            vs.missed(),
+            vs.ballots,
+            vs.missed_percent()
+                .map_or_else(|| "-".to_string(), |percent| format!("{:.1}%", percent)),
+            voting_key,
+            key(vs.mining_key),
+            key(vs.payout_key),
+            escape(&vs.name()),
+            sparkline(stats.ballots(), &stats.voting_keys(voting_key))
+        )?;
+    }
+    writeln!(w, "</tbody>\n</table>")?;
+
+    writeln!(w, "<h2>Turnout</h2>")?;
+    writeln!(w, "{}", turnout_chart(stats.ballots()))?;
+
+    writeln!(w, "<h2>Ballots</h2>")?;
+    writeln!(w, "<table class=\"sortable\">\n<thead><tr>")?;
+    writeln!(
+        w,
+        "<th>ID</th><th>Block</th><th>Start</th><th>End</th><th>Type</th>"
+    )?;
+    writeln!(
+        w,
+        "<th>Affected key</th><th>Validator</th><th>Creator</th><th>Votes</th>"
+    )?;
+    writeln!(w, "<th>Threshold</th><th>Memo</th><th>Missed by</th>")?;
+    writeln!(w, "</tr></thead>\n<tbody>")?;
+    for record in stats.ballots() {
+        let info = &record.info;
+        let missed: Vec<String> = record
+            .missed
+            .iter()
+            .map(|voter| voter_name(stats, voter))
+            .collect();
+        writeln!(
+            w,
+            "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td class=\"key\">{}</td><td>{}</td><td>{}</td>\
+             <td class=\"num\">{}/{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td></tr>",
+            info.id,
+            record.block,
+            util::format_time(info.start_time),
+            util::format_time(info.end_time),
+            escape(&info.description()),
+            escape(&info.subject()),
+            escape(info.validator_name.as_ref().map_or("", String::as_str)),
+            voter_name(stats, &info.creator),
+            record.votes.len(),
+            record.voters.len(),
+            info.min_threshold,
+            escape(&info.memo),
+            missed.join(", ")
+        )?;
+    }
+    writeln!(w, "</tbody>\n</table>")?;
+    writeln!(w, "<script>{}</script>\n</body>\n</html>", SCRIPT)
+}
@@ -0,0 +1,87 @@
+use serde_derive::Serialize;
+use std::fmt::{self, Display, Formatter};
+
+/// The standard normal quantile for a two-sided 95% confidence interval.
+const Z: f64 = 1.96;
+
+/// A 95% confidence interval for a share, between 0 and 1.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Interval {
+    /// Returns the Wilson score interval for `successes` out of `trials`, or `None` if there were
+    /// no trials. Unlike the plain ratio, it is wide for small samples: 1 of 1 gives 21% to 100%.
+    pub fn wilson(successes: usize, trials: usize) -> Option<Interval> {
+        if trials == 0 {
+            return None;
+        }
+        let n = trials as f64;
+        let p = successes as f64 / n;
+        let z2 = Z * Z;
+        let denominator = 1.0 + z2 / n;
+        let center = (p + z2 / (2.0 * n)) / denominator;
+        let margin = Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+        Some(Interval {
+            lower: (center - margin).max(0.0),
+            upper: (center + margin).min(1.0),
+        })
+    }
+
+    /// Returns the interval for the complementary share, e.g. missed instead of voted ballots.
+    pub fn complement(self) -> Interval {
+        Interval {
+            lower: 1.0 - self.upper,
+            upper: 1.0 - self.lower,
+        }
+    }
+}
+
+/// Formats the interval as percentages, e.g. `0.1–2.8%`.
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:.1}–{:.1}%", self.lower * 100.0, self.upper * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interval;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.001,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn wilson() {
+        assert_eq!(None, Interval::wilson(0, 0));
+
+        // A single success: 21% to 100%, not 100%.
+        let interval = Interval::wilson(1, 1).unwrap();
+        assert_close(interval.lower, 0.2065);
+        assert_close(interval.upper, 1.0);
+        assert_eq!("20.7–100.0%", interval.to_string());
+
+        let interval = Interval::wilson(0, 10).unwrap();
+        assert_close(interval.lower, 0.0);
+        assert_close(interval.upper, 0.2775);
+
+        let interval = Interval::wilson(199, 200).unwrap();
+        assert_close(interval.lower, 0.9723);
+        assert_close(interval.upper, 0.9991);
+    }
+
+    #[test]
+    fn complement() {
+        let interval = Interval::wilson(1, 1).unwrap().complement();
+        assert_close(interval.lower, 0.0);
+        assert_close(interval.upper, 0.7935);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::stats::Stats;
+use crate::util;
+use serde_derive::Serialize;
+use std::io::Write;
+
+/// The version of the JSON document format. It must be incremented whenever a field is removed or
+/// its meaning changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// Information about how the statistics were collected.
+#[derive(Serialize)]
+pub struct RunInfo<'a> {
+    /// The JSON-RPC endpoint.
+    pub endpoint: &'a str,
+    /// The file with the contract addresses.
+    pub contracts_file: &'a str,
+}
+
+/// The JSON document, containing the statistics and information about how they were collected.
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u32,
+    /// The time the document was created, as a Unix timestamp.
+    generated_at: u64,
+    run: &'a RunInfo<'a>,
+    #[serde(flatten)]
+    stats: &'a Stats,
+}
+
+/// Writes the statistics as a JSON document.
+pub fn write<W: Write>(writer: W, run: &RunInfo, stats: &Stats) -> serde_json::Result<()> {
+    let document = Document {
+        version: FORMAT_VERSION,
+        generated_at: util::now_timestamp(),
+        run,
+        stats,
+    };
+    serde_json::to_writer_pretty(writer, &document)
+}
@@ -0,0 +1,65 @@
+use crate::util;
+use ethabi::Address;
+use serde_derive::Serialize;
+use std::fmt::{self, Display, Formatter};
+
+/// The type of a validator's key.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    Mining,
+    Voting,
+    Payout,
+}
+
+impl KeyType {
+    /// Returns the key type with the given number, as used in the `VotingToChangeKeys` contract.
+    pub fn from_affected_key_type(n: u64) -> Option<KeyType> {
+        match n {
+            1 => Some(KeyType::Mining),
+            2 => Some(KeyType::Voting),
+            3 => Some(KeyType::Payout),
+            _ => None,
+        }
+    }
+}
+
+impl Display for KeyType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            KeyType::Mining => write!(f, "mining"),
+            KeyType::Voting => write!(f, "voting"),
+            KeyType::Payout => write!(f, "payout"),
+        }
+    }
+}
+
+/// A change of one of a validator's keys, as logged by the `KeysManager` contract.
+#[derive(Clone, Debug, Serialize)]
+pub struct KeyChange {
+    /// The number of the block in which the key was changed.
+    pub block: u64,
+    /// The timestamp of the block in which the key was changed.
+    pub timestamp: u64,
+    pub key_type: KeyType,
+    /// The new or removed key.
+    pub key: Address,
+    /// The validator's mining key. For a mining key change, this is the same as `key`.
+    pub mining_key: Address,
+    /// The action, e.g. `added`, `removed` or `swapped`.
+    pub action: String,
+}
+
+impl Display for KeyChange {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} {} key {:?} {}",
+            self.block,
+            util::format_date(self.timestamp),
+            self.key_type,
+            self.key,
+            self.action
+        )
+    }
+}
@@ -0,0 +1,85 @@
+use serde_derive::Serialize;
+
+/// The number of seconds in a day.
+const DAY: u64 = 24 * 60 * 60;
+
+/// Statistics about how long after the start of a ballot a validator votes.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Latency {
+    /// The number of votes the statistics are based on.
+    pub votes: usize,
+    /// The median time from the start of a ballot until the vote, in seconds.
+    pub median: u64,
+    /// The 90th percentile of the time from the start of a ballot until the vote, in seconds.
+    pub p90: u64,
+    /// The share of votes that were cast less than 24 hours before the end of the ballot,
+    /// between 0 and 1.
+    pub last_day_share: f64,
+}
+
+impl Latency {
+    /// Returns the statistics for the given `(latency, time_left)` pairs of each vote, in
+    /// seconds, or `None` if there are no votes.
+    pub fn new(votes: &[(u64, u64)]) -> Option<Latency> {
+        if votes.is_empty() {
+            return None;
+        }
+        let mut latencies: Vec<u64> = votes.iter().map(|&(latency, _)| latency).collect();
+        latencies.sort_unstable();
+        let last_day = votes.iter().filter(|&&(_, left)| left < DAY).count();
+        Some(Latency {
+            votes: votes.len(),
+            median: percentile(&latencies, 50),
+            p90: percentile(&latencies, 90),
+            last_day_share: last_day as f64 / votes.len() as f64,
+        })
+    }
+}
+
+/// Returns the `p`-th percentile of the sorted, non-empty values, using the nearest-rank method.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (sorted.len() * p + 99) / 100;
+    sorted[rank.max(1) - 1]
+}
+
+/// Formats a number of seconds as a short duration, e.g. `2d 5h`, `3h 12m` or `7m`.
+pub fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / DAY, seconds / 3600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{percentile, Latency, DAY};
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(7, percentile(&[7], 50));
+        assert_eq!(7, percentile(&[7], 90));
+        // With an even count, the nearest rank is the lower of the two middle values.
+        let sorted = [10, 20, 30, 40];
+        assert_eq!(20, percentile(&sorted, 50));
+        assert_eq!(40, percentile(&sorted, 90));
+        assert_eq!(10, percentile(&sorted, 0));
+    }
+
+    #[test]
+    fn latency() {
+        assert!(Latency::new(&[]).is_none());
+
+        let latency = Latency::new(&[(60, DAY - 1)]).unwrap();
+        assert_eq!((1, 60, 60), (latency.votes, latency.median, latency.p90));
+        assert_eq!(1.0, latency.last_day_share);
+
+        let votes = [(40, DAY), (10, 3 * DAY), (30, 0), (20, 2 * DAY)];
+        let latency = Latency::new(&votes).unwrap();
+        assert_eq!((4, 20, 40), (latency.votes, latency.median, latency.p90));
+        assert_eq!(0.25, latency.last_day_share);
+    }
+}
@@ -1,26 +1,77 @@
 #[macro_use(EthabiContract)]
 extern crate ethabi_derive;
 
+mod agreement;
+mod ballot;
+mod bound;
 mod cli;
+mod config;
 mod contracts;
 mod counter;
+mod csv;
 mod error;
+mod html;
+mod interval;
+mod json;
+mod keys;
+mod latency;
+mod markdown;
+mod period;
+mod prometheus;
+mod report;
 mod stats;
+mod table;
 mod util;
 mod validator;
 
+use crate::bound::Bound;
+use crate::config::{ColorChoice, Format};
+use crate::period::Window;
+use crate::stats::{DisplayOptions, InactivityChecks};
+use crate::table::{Column, SortKey};
+use crate::validator::MetadataChecks;
+use std::env;
 use std::fs::File;
-use std::time::SystemTime;
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The half-life of the participation score, if sorted by score but no half-life is given.
+const DEFAULT_HALF_LIFE: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+/// Validators with fewer eligible ballots are marked, if no minimum sample size is given.
+const DEFAULT_MIN_SAMPLE: usize = 5;
 
 fn main() {
     let matches = cli::get_matches();
 
-    let url = matches.value_of("url").unwrap_or("http://127.0.0.1:8545");
+    // Command line arguments and environment variables take precedence over the profile.
+    let mut profile = config::load_profile(matches.value_of("config"), matches.value_of("profile"))
+        .expect("load configuration file");
+    // A range given on the command line replaces the profile's range instead of being combined.
+    if ["period", "block", "since", "until"]
+        .iter()
+        .any(|name| matches.is_present(name))
+    {
+        profile.clear_range();
+    }
+
+    let url = matches
+        .value_of("url")
+        .or(profile.url.as_deref())
+        .unwrap_or("http://127.0.0.1:8545");
     let contract_file = matches
         .value_of("contracts")
-        .unwrap_or("contracts/core.json");
-    let file = File::open(contract_file).expect("open contracts file");
+        .map(str::to_string)
+        .or_else(|| matches.value_of("network").map(config::network_file))
+        .or_else(|| profile.contracts_file())
+        .unwrap_or_else(|| config::network_file("core"));
+    let file = File::open(&contract_file).expect("open contracts file");
     let contract_addrs = serde_json::from_reader(file).expect("parse contracts file");
+    let format: Format = matches
+        .value_of("format")
+        .or(profile.format.as_deref())
+        .unwrap_or("text")
+        .parse()
+        .expect("parse output format");
 
     let mut counter = counter::Counter::new(url, contract_addrs);
 
@@ -28,20 +79,197 @@ fn main() {
         counter.set_verbose();
     }
 
-    if let Some(period) = matches.value_of("period") {
+    let period = matches.value_of("period").or(profile.period.as_deref());
+    if let Some(period) = period {
         let duration = parse_duration::parse(period)
             .expect("period must be in the format '5 days', '2 months', etc.");
-        counter.set_start_time(SystemTime::now() - duration);
+        let start = SystemTime::now() - duration;
+        let start = start.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        counter.set_since(Bound::Time(start));
     }
 
     if let Some(start_block) = matches.value_of("block") {
-        counter.set_start_block(
+        counter.set_since(Bound::Block(
             start_block
                 .parse()
                 .expect("block number must be a non-negative integer"),
-        );
+        ));
+    } else if let Some(start_block) = profile.block {
+        counter.set_since(Bound::Block(start_block));
+    }
+
+    if let Some(since) = matches.value_of("since").or(profile.since.as_deref()) {
+        counter.set_since(since.parse().expect("parse start of the range"));
+    }
+    if let Some(until) = matches.value_of("until").or(profile.until.as_deref()) {
+        counter.set_until(until.parse().expect("parse end of the range"));
+    }
+
+    let windows = match matches.value_of("windows").or(profile.windows.as_deref()) {
+        Some(windows) => Window::parse_list(windows).expect("parse windows"),
+        None => Vec::new(),
+    };
+
+    let mut metadata_checks = MetadataChecks::default();
+    let expiry_window = matches
+        .value_of("expiry-window")
+        .or(profile.expiry_window.as_deref());
+    if let Some(expiry_window) = expiry_window {
+        metadata_checks.expiry_window = parse_duration::parse(expiry_window)
+            .expect("expiry window must be in the format '5 days', '2 months', etc.");
+    }
+    let stale_after = matches
+        .value_of("stale-after")
+        .or(profile.stale_after.as_deref());
+    if let Some(stale_after) = stale_after {
+        metadata_checks.stale_after = parse_duration::parse(stale_after)
+            .expect("stale metadata period must be in the format '5 days', '2 months', etc.");
     }
 
-    let stats = counter.count_votes().expect("count votes");
-    println!("{}", stats);
+    let mut inactivity_checks = InactivityChecks::default();
+    if let Some(n) = matches.value_of("inactive-ballots") {
+        inactivity_checks.ballots = n
+            .parse()
+            .expect("number of ballots must be a non-negative integer");
+    } else if let Some(n) = profile.inactive_ballots {
+        inactivity_checks.ballots = n;
+    }
+    let inactive_after = matches
+        .value_of("inactive-after")
+        .or(profile.inactive_after.as_deref());
+    if let Some(inactive_after) = inactive_after {
+        inactivity_checks.period = parse_duration::parse(inactive_after)
+            .expect("inactivity period must be in the format '5 days', '2 months', etc.");
+    }
+
+    let sort: SortKey = matches
+        .value_of("sort")
+        .or(profile.sort.as_deref())
+        .unwrap_or("missed")
+        .parse()
+        .expect("parse sort key");
+    let min_ballots = match matches.value_of("min-ballots") {
+        Some(n) => n
+            .parse()
+            .expect("minimum number of ballots must be a non-negative integer"),
+        None => profile.min_ballots.unwrap_or(0),
+    };
+    let min_sample = match matches.value_of("min-sample") {
+        Some(n) => n
+            .parse()
+            .expect("minimum sample size must be a non-negative integer"),
+        None => profile.min_sample.unwrap_or(DEFAULT_MIN_SAMPLE),
+    };
+    let half_life = matches
+        .value_of("half-life")
+        .or(profile.half_life.as_deref())
+        .map(|half_life| {
+            parse_duration::parse(half_life)
+                .expect("half-life must be in the format '5 days', '2 months', etc.")
+        })
+        .or_else(|| Some(DEFAULT_HALF_LIFE).filter(|_| sort == SortKey::Score));
+    let columns = match matches.value_of("columns").or(profile.columns.as_deref()) {
+        Some(columns) => Column::parse_list(columns).expect("parse columns"),
+        None => {
+            let mut columns = Column::DEFAULT.to_vec();
+            // Show the interval and score next to the raw percentage of missed ballots.
+            if half_life.is_some() {
+                columns.insert(2, Column::Score);
+            }
+            if sort == SortKey::Wilson {
+                columns.insert(2, Column::Interval);
+            }
+            if matches.is_present("former") {
+                columns.push(Column::Removed);
+            }
+            columns
+        }
+    };
+
+    let color: ColorChoice = matches
+        .value_of("color")
+        .or(profile.color.as_deref())
+        .unwrap_or("auto")
+        .parse()
+        .expect("parse color choice");
+    let is_tty = atty::is(atty::Stream::Stdout);
+    colored::control::set_override(match color {
+        ColorChoice::Auto => is_tty && env::var_os("NO_COLOR").is_none(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    });
+    let width = if is_tty {
+        term_size::dimensions_stdout().map(|(width, _)| width)
+    } else {
+        None
+    };
+
+    let bucket = matches
+        .value_of("bucket")
+        .or(profile.bucket.as_deref())
+        .map(|bucket| bucket.parse().expect("parse bucket length"));
+
+    let agreement = if matches.is_present("agreement") {
+        let threshold = matches
+            .value_of("bloc-threshold")
+            .map(|t| t.parse().expect("bloc threshold must be a percentage"))
+            .or(profile.bloc_threshold)
+            .unwrap_or(90.0);
+        Some(threshold / 100.0)
+    } else {
+        None
+    };
+
+    let mut stats = counter.count_votes().expect("count votes");
+    stats.set_display_options(DisplayOptions {
+        exclude: profile.exclude.iter().cloned().collect(),
+        colors: profile.colors.unwrap_or_default(),
+        details: matches.is_present("details"),
+        metadata_checks,
+        inactivity_checks,
+        sort,
+        min_ballots,
+        only_missing: matches.is_present("only-missing"),
+        filter: matches.value_of("filter").map(str::to_string),
+        columns,
+        width,
+        bucket,
+        by_type: matches.is_present("by-type"),
+        agreement,
+        quorum: matches.is_present("quorum"),
+        creators: matches.is_present("creators"),
+        half_life,
+        min_sample,
+        former: matches.is_present("former"),
+        windows,
+    });
+    if let Some(query) = matches.value_of("validator") {
+        report::write(io::stdout(), &stats, query).expect("write validator report");
+    } else {
+        match format {
+            Format::Text => println!("{}", stats),
+            Format::Json => {
+                let run = json::RunInfo {
+                    endpoint: url,
+                    contracts_file: &contract_file,
+                };
+                json::write(io::stdout(), &run, &stats).expect("write JSON output");
+                println!();
+            }
+            Format::Csv => csv::write_validators(io::stdout(), &stats).expect("write CSV output"),
+            Format::Markdown => {
+                let appendix = matches.is_present("appendix");
+                markdown::write(io::stdout(), &stats, appendix).expect("write markdown output");
+            }
+            Format::Html => html::write(io::stdout(), &stats).expect("write HTML output"),
+            Format::Prometheus => {
+                prometheus::write(io::stdout(), &stats).expect("write Prometheus metrics")
+            }
+        }
+    }
+
+    if let Some(path) = matches.value_of("ballots-csv") {
+        let file = File::create(path).expect("create ballots CSV file");
+        csv::write_ballots(file, &stats).expect("write ballots CSV file");
+    }
 }
@@ -0,0 +1,80 @@
+use crate::stats::Stats;
+use crate::util;
+use std::io::{self, Write};
+
+/// Escapes characters that would break a markdown table cell or emphasis.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '|' || c == '*' || c == '_' || c == '`' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Writes the statistics as a GitHub-flavored markdown table, preceded by a header with the
+/// period and block range. If `appendix` is `true`, a list of ballots and the validators who
+/// missed them follows the table.
+pub fn write<W: Write>(mut writer: W, stats: &Stats, appendix: bool) -> io::Result<()> {
+    writeln!(writer, "## POA ballot statistics")?;
+    writeln!(writer)?;
+    writeln!(writer, "{}", stats.range().description())?;
+    writeln!(writer)?;
+    writeln!(
+        writer,
+        "| Missed | Missed % | Voting key | Mining key | Payout key | Name |"
+    )?;
+    writeln!(writer, "|---:|---:|---|---|---|---|")?;
+    for (voting_key, vs) in stats.listed() {
+        let key = |key: Option<_>| key.map_or_else(String::new, |k| format!("`{}`", k));
+        writeln!(
+            writer,
+            "| {}/{} | {} | `{}` | {} | {} | {} |",
+            vs.missed(),
+            vs.ballots,
+            vs.missed_percent()
+                .map_or_else(|| "-".to_string(), |percent| format!("{:.1}%", percent)),
+            voting_key,
+            key(vs.mining_key),
+            key(vs.payout_key),
+            escape(&vs.name())
+        )?;
+    }
+    if !appendix {
+        return Ok(());
+    }
+    writeln!(writer)?;
+    writeln!(writer, "### Ballots")?;
+    writeln!(writer)?;
+    for record in stats.ballots() {
+        let info = &record.info;
+        write!(
+            writer,
+            "* **Ballot {}** (block {}, {}): {} `{}`",
+            info.id,
+            record.block,
+            util::format_date(info.start_time),
+            info.description(),
+            info.subject()
+        )?;
+        if let Some(ref name) = info.validator_name {
+            write!(writer, " ({})", escape(name))?;
+        }
+        let missed: Vec<String> = record
+            .missed
+            .iter()
+            .map(|voter| match stats.get(voter).map(|vs| vs.name()) {
+                Some(ref name) if !name.is_empty() => escape(name),
+                _ => format!("`{:?}`", voter),
+            })
+            .collect();
+        if missed.is_empty() {
+            writeln!(writer, "  \n  Missed by nobody.")?;
+        } else {
+            writeln!(writer, "  \n  Missed by: {}", missed.join(", "))?;
+        }
+    }
+    Ok(())
+}
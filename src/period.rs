@@ -0,0 +1,178 @@
+use crate::ballot::BallotRecord;
+use crate::error::{Error, ErrorKind};
+use crate::stats::Participation;
+use chrono::{Datelike, TimeZone, Utc};
+use ethabi::Address;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The length of the periods into which ballots are grouped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bucket {
+    Week,
+    Month,
+    Quarter,
+}
+
+impl Bucket {
+    /// The names of all bucket lengths.
+    pub const NAMES: &'static [&'static str] = &["week", "month", "quarter"];
+
+    /// Returns the name of the period containing the given Unix timestamp, e.g. `2018-W07`,
+    /// `2018-02` or `2018-Q1`. The names sort chronologically.
+    pub fn label(self, timestamp: u64) -> String {
+        let time = match Utc.timestamp_opt(timestamp as i64, 0).single() {
+            Some(time) => time,
+            None => return "-".to_string(),
+        };
+        match self {
+            Bucket::Week => {
+                let week = time.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Bucket::Month => time.format("%Y-%m").to_string(),
+            Bucket::Quarter => format!("{}-Q{}", time.year(), time.month0() / 3 + 1),
+        }
+    }
+
+    /// Returns the name of the bucket length, e.g. `month`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Bucket::Week => "week",
+            Bucket::Month => "month",
+            Bucket::Quarter => "quarter",
+        }
+    }
+}
+
+impl FromStr for Bucket {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Bucket, Error> {
+        match s {
+            "week" => Ok(Bucket::Week),
+            "month" => Ok(Bucket::Month),
+            "quarter" => Ok(Bucket::Quarter),
+            _ => Err(ErrorKind::UnknownBucket(s.to_string()).into()),
+        }
+    }
+}
+
+/// Returns the names of all periods in which ballots were created, in chronological order.
+pub fn labels(ballots: &[BallotRecord], bucket: Bucket) -> Vec<String> {
+    let labels: BTreeSet<String> = ballots
+        .iter()
+        .map(|record| bucket.label(record.timestamp))
+        .collect();
+    labels.into_iter().collect()
+}
+
+/// Returns the participation of the voter with the given voting keys in each period in which
+/// they were allowed to vote.
+pub fn participation(
+    ballots: &[BallotRecord],
+    keys: &[Address],
+    bucket: Bucket,
+) -> BTreeMap<String, Participation> {
+    let mut periods = BTreeMap::new();
+    for record in ballots {
+        let voted = match record.participation(keys) {
+            Some(voted) => voted,
+            None => continue,
+        };
+        let label = bucket.label(record.timestamp);
+        periods
+            .entry(label)
+            .or_insert_with(Participation::default)
+            .add(voted);
+    }
+    periods
+}
+
+/// A period that ends at the end of the counted range, e.g. the last 30 days, or all ballots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Window {
+    /// The window's name, as given, e.g. `30d` or `all`.
+    pub label: String,
+    /// The window's length, or `None` if it contains all ballots.
+    pub length: Option<Duration>,
+}
+
+impl Window {
+    /// Parses a comma-separated list of windows, and sorts them from the shortest to `all`, so
+    /// that each window can be compared with the next, longer one.
+    pub fn parse_list(s: &str) -> Result<Vec<Window>, Error> {
+        let mut windows = s
+            .split(',')
+            .map(|window| window.trim().parse())
+            .collect::<Result<Vec<Window>, Error>>()?;
+        windows.sort_by_key(|window| (window.length.is_none(), window.length));
+        Ok(windows)
+    }
+}
+
+/// Parses `all`, or a duration, e.g. `30d` or `6 months`.
+impl FromStr for Window {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Window, Error> {
+        let length = match s {
+            "all" => None,
+            _ => match parse_duration::parse(s) {
+                Ok(length) => Some(length),
+                Err(_) => return Err(ErrorKind::InvalidWindow(s.to_string()).into()),
+            },
+        };
+        Ok(Window {
+            label: s.to_string(),
+            length,
+        })
+    }
+}
+
+/// Returns the participation of the voter with the given voting keys in each window ending at
+/// `end`, by window label.
+pub fn window_participation(
+    ballots: &[BallotRecord],
+    keys: &[Address],
+    windows: &[Window],
+    end: u64,
+) -> BTreeMap<String, Participation> {
+    let mut participation = BTreeMap::new();
+    for record in ballots {
+        let voted = match record.participation(keys) {
+            Some(voted) => voted,
+            None => continue,
+        };
+        for window in windows {
+            let start = window
+                .length
+                .map_or(0, |length| end.saturating_sub(length.as_secs()));
+            if record.timestamp >= start {
+                participation
+                    .entry(window.label.clone())
+                    .or_insert_with(Participation::default)
+                    .add(voted);
+            }
+        }
+    }
+    participation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Window;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_windows() {
+        let windows = Window::parse_list("all, 90d,30d").unwrap();
+        let labels: Vec<&str> = windows.iter().map(|w| w.label.as_str()).collect();
+        assert_eq!(vec!["30d", "90d", "all"], labels);
+        let day = 24 * 60 * 60;
+        assert_eq!(Some(Duration::from_secs(30 * day)), windows[0].length);
+        assert_eq!(None, windows[2].length);
+        assert!(Window::parse_list("30d,forever").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::stats::Stats;
+use crate::util;
+use ethabi::Address;
+use std::io::{self, Write};
+
+/// The prefix of all metric names.
+const PREFIX: &str = "poa_ballot_stats";
+
+/// Escapes a label value: backslashes, double quotes and line feeds.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes the `HELP` and `TYPE` lines of a gauge.
+fn write_header<W: Write>(w: &mut W, name: &str, help: &str) -> io::Result<()> {
+    writeln!(w, "# HELP {}_{} {}", PREFIX, name, help)?;
+    writeln!(w, "# TYPE {}_{} gauge", PREFIX, name)
+}
+
+/// Writes the statistics in the Prometheus text exposition format, e.g. for the node exporter's
+/// textfile collector.
+pub fn write<W: Write>(mut w: W, stats: &Stats) -> io::Result<()> {
+    let validators: Vec<(String, &_)> = stats
+        .listed()
+        .into_iter()
+        .map(|(voting_key, vs)| {
+            let mining_key = vs.mining_key.unwrap_or_else(Address::zero);
+            let labels = format!(
+                "voting_key=\"{:?}\",mining_key=\"{:?}\",name=\"{}\"",
+                voting_key,
+                mining_key,
+                escape(&vs.name())
+            );
+            (labels, vs)
+        })
+        .collect();
+
+    let help = "The number of ballots the validator was allowed to vote on.";
+    write_header(&mut w, "validator_ballots", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_ballots{{{}}} {}",
+            PREFIX, labels, vs.ballots
+        )?;
+    }
+    let help = "The number of votes the validator cast.";
+    write_header(&mut w, "validator_votes", help)?;
+    for (labels, vs) in &validators {
+        writeln!(w, "{}_validator_votes{{{}}} {}", PREFIX, labels, vs.voted)?;
+    }
+    let help = "The share of ballots the validator missed, between 0 and 1.";
+    write_header(&mut w, "validator_missed_ratio", help)?;
+    for (labels, vs) in &validators {
+        // Validators without eligible ballots have no share.
+        let ratio = match vs.missed_percent() {
+            Some(percent) => percent / 100.0,
+            None => continue,
+        };
+        writeln!(
+            w,
+            "{}_validator_missed_ratio{{{}}} {}",
+            PREFIX, labels, ratio
+        )?;
+    }
+
+    if let Some(half_life) = stats.display_options().half_life {
+        let now = util::now_timestamp();
+        let help = "The share of ballots the validator voted on, weighted by recency, between 0 \
+                    and 1.";
+        write_header(&mut w, "validator_score", help)?;
+        for (labels, vs) in &validators {
+            if let Some(score) = vs.score(now, half_life) {
+                let score = score / 100.0;
+                writeln!(w, "{}_validator_score{{{}}} {}", PREFIX, labels, score)?;
+            }
+        }
+    }
+
+    let help = "The number of consecutive ballots, up to the latest one, the validator missed.";
+    write_header(&mut w, "validator_missed_streak", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_missed_streak{{{}}} {}",
+            PREFIX, labels, vs.current_streak
+        )?;
+    }
+    let help = "The number of rejected ballots the validator missed.";
+    write_header(&mut w, "validator_failed_absent", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_failed_absent{{{}}} {}",
+            PREFIX, labels, vs.failed_absent
+        )?;
+    }
+    let help = "The number of rejected ballots the validator missed, where the absent validators \
+                could have changed the result.";
+    write_header(&mut w, "validator_decisive_absent", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_decisive_absent{{{}}} {}",
+            PREFIX, labels, vs.decisive_absent
+        )?;
+    }
+    let help = "The number of ballots of the given type the validator created.";
+    write_header(&mut w, "validator_created_ballots", help)?;
+    for (labels, vs) in &validators {
+        for (category, n) in &vs.created.by_type {
+            writeln!(
+                w,
+                "{}_validator_created_ballots{{{},ballot_type=\"{}\"}} {}",
+                PREFIX,
+                labels,
+                escape(category),
+                n
+            )?;
+        }
+    }
+    let help = "The number of ballots created by the validator that were accepted.";
+    write_header(&mut w, "validator_created_accepted", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_created_accepted{{{}}} {}",
+            PREFIX, labels, vs.created.accepted
+        )?;
+    }
+    let help = "The number of ballots created by the validator that were rejected.";
+    write_header(&mut w, "validator_created_rejected", help)?;
+    for (labels, vs) in &validators {
+        writeln!(
+            w,
+            "{}_validator_created_rejected{{{}}} {}",
+            PREFIX, labels, vs.created.rejected
+        )?;
+    }
+    let help = "The number of currently open ballots created by the validator.";
+    write_header(&mut w, "validator_open_ballots", help)?;
+    for (labels, vs) in &validators {
+        if let Some(open) = vs.created.open {
+            writeln!(
+                w,
+                "{}_validator_open_ballots{{{}}} {}",
+                PREFIX, labels, open
+            )?;
+        }
+    }
+    let help = "The median time from the start of a ballot until the validator's vote.";
+    write_header(&mut w, "validator_median_latency_seconds", help)?;
+    for (labels, vs) in &validators {
+        if let Some(latency) = vs.latency() {
+            writeln!(
+                w,
+                "{}_validator_median_latency_seconds{{{}}} {}",
+                PREFIX, labels, latency.median
+            )?;
+        }
+    }
+    let help =
+        "The 90th percentile of the time from the start of a ballot until the validator's vote.";
+    write_header(&mut w, "validator_p90_latency_seconds", help)?;
+    for (labels, vs) in &validators {
+        if let Some(latency) = vs.latency() {
+            writeln!(
+                w,
+                "{}_validator_p90_latency_seconds{{{}}} {}",
+                PREFIX, labels, latency.p90
+            )?;
+        }
+    }
+    let help = "The share of the validator's votes cast in the last 24 hours of a ballot.";
+    write_header(&mut w, "validator_last_day_vote_ratio", help)?;
+    for (labels, vs) in &validators {
+        if let Some(latency) = vs.latency() {
+            writeln!(
+                w,
+                "{}_validator_last_day_vote_ratio{{{}}} {}",
+                PREFIX, labels, latency.last_day_share
+            )?;
+        }
+    }
+    let help = "The number of ballots of the given type the validator was allowed to vote on.";
+    write_header(&mut w, "validator_type_ballots", help)?;
+    for (labels, vs) in &validators {
+        for (category, p) in &vs.by_type {
+            writeln!(
+                w,
+                "{}_validator_type_ballots{{{},ballot_type=\"{}\"}} {}",
+                PREFIX,
+                labels,
+                escape(category),
+                p.ballots
+            )?;
+        }
+    }
+    let help = "The number of votes the validator cast on ballots of the given type.";
+    write_header(&mut w, "validator_type_votes", help)?;
+    for (labels, vs) in &validators {
+        for (category, p) in &vs.by_type {
+            writeln!(
+                w,
+                "{}_validator_type_votes{{{},ballot_type=\"{}\"}} {}",
+                PREFIX,
+                labels,
+                escape(category),
+                p.voted
+            )?;
+        }
+    }
+
+    let help = "The number of ballots that were counted.";
+    write_header(&mut w, "ballots", help)?;
+    writeln!(w, "{}_ballots {}", PREFIX, stats.ballots().len())?;
+    let help = "The number of ballots that are currently open for voting.";
+    write_header(&mut w, "active_ballots", help)?;
+    writeln!(w, "{}_active_ballots {}", PREFIX, stats.active_ballots())?;
+    if let Some(limit) = stats.ballot_limit() {
+        let help = "The maximum number of open ballots per validator.";
+        write_header(&mut w, "ballot_limit", help)?;
+        writeln!(w, "{}_ballot_limit {}", PREFIX, limit)?;
+    }
+    let help = "The number of the latest block that was processed.";
+    write_header(&mut w, "latest_block", help)?;
+    writeln!(w, "{}_latest_block {}", PREFIX, stats.range().to_block)
+}
@@ -0,0 +1,178 @@
+use crate::ballot::BallotRecord;
+use crate::error::{Error, ErrorKind};
+use crate::latency;
+use crate::stats::{Stats, VoterStats};
+use crate::util;
+use colored::Colorize;
+use std::io::Write;
+use web3::types::Address;
+
+/// Writes the full record of the validator with the given voting or mining key or name: their
+/// keys, metadata and key history, and every ballot they were allowed to vote on, with their vote.
+pub fn write<W: Write>(mut writer: W, stats: &Stats, query: &str) -> Result<(), Error> {
+    let voter = match stats.find(query)[..] {
+        [voter] => *voter,
+        [] => return Err(ErrorKind::ValidatorNotFound(query.to_string()).into()),
+        ref voters => {
+            let mut names = voters
+                .iter()
+                .map(|voter| {
+                    Ok(format!(
+                        "{} ({:?})",
+                        voter_stats(stats, voter)?.name(),
+                        voter
+                    ))
+                })
+                .collect::<Result<Vec<String>, Error>>()?;
+            names.sort();
+            let names = names.join(", ");
+            return Err(ErrorKind::AmbiguousValidator(query.to_string(), names).into());
+        }
+    };
+    let vs = voter_stats(stats, &voter)?;
+    write!(writer, "{}", vs.details(&voter))?;
+    match (vs.tenure.added, vs.tenure.removed) {
+        (Some(added), Some(removed)) => writeln!(
+            writer,
+            "  Validator from {} to {}.",
+            util::format_date(added),
+            util::format_date(removed)
+        )?,
+        (Some(added), None) => writeln!(writer, "  Validator since {}.", util::format_date(added))?,
+        (None, Some(removed)) => writeln!(
+            writer,
+            "  Validator removed on {}.",
+            util::format_date(removed)
+        )?,
+        (None, None) => (),
+    }
+
+    // Ballots on which the validator voted with an earlier voting key count as well, as they do
+    // in the voter's statistics.
+    let keys = stats.voting_keys(&voter);
+    let eligible: Vec<&BallotRecord> = stats
+        .ballots()
+        .iter()
+        .filter(|record| record.voters.iter().any(|v| keys.contains(v)))
+        .collect();
+    let interval = vs
+        .missed_interval()
+        .map_or_else(String::new, |i| format!(", 95% CI {}", i));
+    writeln!(
+        writer,
+        "\nVoted on {} of {} ballots, missed {} ({}{}).",
+        vs.voted,
+        vs.ballots,
+        vs.missed(),
+        vs.missed_percent()
+            .map_or_else(|| "-".to_string(), |percent| format!("{:.1}%", percent)),
+        interval
+    )?;
+    if vs.ballots < stats.display_options().min_sample {
+        writeln!(writer, "Too few ballots for a reliable share.")?;
+    }
+    if let Some(half_life) = stats.display_options().half_life {
+        if let Some(score) = vs.score(util::now_timestamp(), half_life) {
+            writeln!(
+                writer,
+                "Recency-weighted participation score: {:.1}%.",
+                score
+            )?;
+        }
+    }
+    let last_vote = vs
+        .last_vote
+        .map_or_else(|| "never".to_string(), util::format_date);
+    writeln!(
+        writer,
+        "Missed the last {} ballots in a row, at most {}; last vote: {}.",
+        vs.current_streak, vs.longest_streak, last_vote
+    )?;
+    let failed: Vec<&&BallotRecord> = eligible
+        .iter()
+        .filter(|record| record.participation(&keys) == Some(false))
+        .filter(|record| record.votes_short.is_some())
+        .collect();
+    if !failed.is_empty() {
+        let decisive = failed
+            .iter()
+            .filter(|record| record.abstainers_decisive())
+            .count();
+        writeln!(
+            writer,
+            "Absent from {} rejected ballots; the absent validators could have passed {} of them.",
+            failed.len(),
+            decisive
+        )?;
+    }
+    let created = &vs.created;
+    if created.total() > 0 {
+        let success = created
+            .success_rate()
+            .map_or_else(String::new, |rate| format!(" ({:.0}%)", rate * 100.0));
+        writeln!(
+            writer,
+            "Created {} ballots: {} accepted, {} rejected{}; voted on {} of them.",
+            created.total(),
+            created.accepted,
+            created.rejected,
+            success,
+            created.voted_own
+        )?;
+    }
+    match (created.open, stats.ballot_limit()) {
+        (Some(open), Some(limit)) => {
+            writeln!(writer, "Open ballots: {} of at most {}.", open, limit)?
+        }
+        (Some(open), None) => writeln!(writer, "Open ballots: {}.", open)?,
+        (None, _) => (),
+    }
+    if let Some(latency) = vs.latency() {
+        writeln!(
+            writer,
+            "Median time to vote {}, 90th percentile {}; {:.0}% of votes in the last 24 hours.",
+            latency::format_duration(latency.median),
+            latency::format_duration(latency.p90),
+            latency.last_day_share * 100.0
+        )?;
+    }
+    if eligible.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(writer, "\n{}", "Ballots".bold())?;
+    let header = format!(
+        "{:>9}  {:10}  {:>5}  {:22}  {:42}  {:11}  {}",
+        "Block", "Date", "ID", "Type", "Affected", "Result", "Vote"
+    );
+    writeln!(writer, "{}", header.bold())?;
+    for record in eligible {
+        let info = &record.info;
+        let vote = record.votes.iter().find(|vote| keys.contains(&vote.voter));
+        let vote_text = match vote {
+            Some(vote) => {
+                format!("{}, {}", vote.decision, util::format_time(vote.time)).bright_green()
+            }
+            None => "missed".bright_red(),
+        };
+        writeln!(
+            writer,
+            "{:>9}  {:10}  {:>5}  {:22}  {:42}  {:11}  {}",
+            record.block,
+            util::format_date(info.start_time),
+            info.id,
+            info.description(),
+            info.subject(),
+            info.quorum_state.to_string(),
+            vote_text
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the statistics of the given voter, or an error if there are none.
+fn voter_stats<'a>(stats: &'a Stats, voter: &Address) -> Result<&'a VoterStats, Error> {
+    stats
+        .get(voter)
+        .ok_or_else(|| ErrorKind::ValidatorNotFound(format!("{:?}", voter)).into())
+}
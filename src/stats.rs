@@ -1,45 +1,554 @@
-use crate::validator::Validator;
+use crate::agreement::AgreementMatrix;
+use crate::ballot::{BallotInfo, BallotRecord, Decision, QuorumState, Vote};
+use crate::interval::Interval;
+use crate::keys::{KeyChange, KeyType};
+use crate::latency::{self, Latency};
+use crate::period::{self, Bucket, Window};
+use crate::table::{Column, SortKey, Table};
+use crate::util;
+use crate::validator::{MetadataChecks, Validator};
 use colored::{Color, Colorize};
 use ethabi::Address;
-use std::collections::HashMap;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Display, Formatter};
+use std::mem;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The maximum width of the name column in the tables of participation by period or type.
+const MAX_MATRIX_NAME_WIDTH: usize = 24;
 
 /// The count of ballots and cast votes, as well as metadata for a particular voter.
-#[derive(Clone, Default)]
-struct VoterStats {
+#[derive(Clone, Default, Serialize)]
+pub struct VoterStats {
     /// The number of ballots where this voter had a right to vote.
-    ballots: usize,
+    pub ballots: usize,
     /// The number of votes cast by this voter.
-    voted: usize,
+    pub voted: usize,
     /// The validator metadata.
-    validator: Option<Validator>,
+    pub validator: Option<Validator>,
     /// The mining key.
-    mining_key: Option<Address>,
+    pub mining_key: Option<Address>,
+    /// The payout key, if the validator has one.
+    pub payout_key: Option<Address>,
+    /// All changes of the validator's keys.
+    pub key_history: Vec<KeyChange>,
+    /// The participation in each category of ballots, e.g. `add voting key`.
+    pub by_type: BTreeMap<String, Participation>,
+    /// The time since the ballot's start, and the time until its end, of each vote, in seconds.
+    #[serde(skip)]
+    vote_times: Vec<(u64, u64)>,
+    /// The timestamp of each eligible ballot, and whether this voter voted on it.
+    #[serde(skip)]
+    ballot_times: Vec<(u64, bool)>,
+    /// The number of consecutive ballots, up to the latest one, that this voter missed.
+    pub current_streak: usize,
+    /// The longest run of consecutive ballots that this voter missed.
+    pub longest_streak: usize,
+    /// The time of this voter's latest vote, as a Unix timestamp.
+    pub last_vote: Option<u64>,
+    /// The ballots this voter created.
+    pub created: Proposals,
+    /// When the validator's keys were added, and this voting key removed.
+    pub tenure: Tenure,
+    /// The number of rejected ballots this voter missed.
+    pub failed_absent: usize,
+    /// The number of rejected ballots this voter missed, where the votes of the absent validators
+    /// could have changed the result.
+    pub decisive_absent: usize,
+}
+
+impl VoterStats {
+    /// Returns the validator's name, or an empty string if unknown.
+    pub fn name(&self) -> String {
+        self.validator
+            .as_ref()
+            .map_or_else(String::new, Validator::name)
+    }
+
+    /// Returns the number of ballots this voter missed.
+    pub fn missed(&self) -> usize {
+        self.ballots - self.voted
+    }
+
+    /// Returns the percentage of ballots this voter missed, or `None` if they weren't eligible
+    /// for any ballots.
+    pub fn missed_percent(&self) -> Option<f64> {
+        if self.ballots == 0 {
+            None
+        } else {
+            Some(self.missed() as f64 * 100.0 / self.ballots as f64)
+        }
+    }
+
+    /// Returns the 95% Wilson score interval of the share of missed ballots, or `None` if the
+    /// voter wasn't eligible for any ballots.
+    pub fn missed_interval(&self) -> Option<Interval> {
+        Interval::wilson(self.voted, self.ballots).map(Interval::complement)
+    }
+
+    /// Returns a description of why the voter is considered inactive, if they are.
+    pub fn inactivity(&self, now: u64, checks: &InactivityChecks) -> Option<String> {
+        if checks.ballots > 0 && self.current_streak >= checks.ballots {
+            return Some(format!("Missed the last {} ballots", self.current_streak));
+        }
+        let period = checks.period.as_secs();
+        match self.last_vote {
+            Some(last_vote) if now.saturating_sub(last_vote) > period => {
+                let date = util::format_date(last_vote);
+                Some(format!("No vote since {}", date))
+            }
+            Some(_) => None,
+            // Without any vote, the inactivity counts from the first eligible ballot.
+            None => {
+                let first_ballot = self.ballot_times.iter().map(|&(time, _)| time).min()?;
+                if now.saturating_sub(first_ballot) > period {
+                    Some("No vote in the counted range".to_string())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns the percentage of eligible ballots this voter voted on, where each ballot is
+    /// weighted by `0.5^(age / half_life)`, or `None` if there were no eligible ballots.
+    pub fn score(&self, now: u64, half_life: Duration) -> Option<f64> {
+        let half_life = half_life.as_secs().max(1) as f64;
+        let (mut total, mut voted) = (0.0, 0.0);
+        for &(timestamp, has_voted) in &self.ballot_times {
+            let age = now.saturating_sub(timestamp) as f64;
+            let weight = 0.5f64.powf(age / half_life);
+            total += weight;
+            if has_voted {
+                voted += weight;
+            }
+        }
+        if self.ballot_times.is_empty() {
+            None
+        } else if total == 0.0 {
+            // All ballots are so old that their weights underflow.
+            self.missed_percent().map(|missed| 100.0 - missed)
+        } else {
+            Some(voted * 100.0 / total)
+        }
+    }
+
+    /// Sets the current and longest streak of missed ballots from the list of eligible ballots.
+    fn recount_streaks(&mut self) {
+        self.current_streak = 0;
+        self.longest_streak = 0;
+        for &(_, voted) in &self.ballot_times {
+            if voted {
+                self.current_streak = 0;
+            } else {
+                self.current_streak += 1;
+                self.longest_streak = self.longest_streak.max(self.current_streak);
+            }
+        }
+    }
+
+    /// Adds the ballots and votes counted for another voting key of the same validator.
+    fn merge(&mut self, other: VoterStats) {
+        self.ballots += other.ballots;
+        self.voted += other.voted;
+        for (category, p) in other.by_type {
+            let entry = self.by_type.entry(category).or_default();
+            entry.ballots += p.ballots;
+            entry.voted += p.voted;
+        }
+        self.vote_times.extend(other.vote_times);
+        self.ballot_times.extend(other.ballot_times);
+        self.ballot_times.sort_by_key(|&(timestamp, _)| timestamp);
+        self.recount_streaks();
+        self.last_vote = self.last_vote.max(other.last_vote);
+        self.created.merge(other.created);
+        if let Some(added) = other.tenure.added {
+            self.tenure.add(added);
+        }
+        self.failed_absent += other.failed_absent;
+        self.decisive_absent += other.decisive_absent;
+    }
+
+    /// Returns statistics about how soon after the start of a ballot this voter votes.
+    pub fn latency(&self) -> Option<Latency> {
+        Latency::new(&self.vote_times)
+    }
+
+    /// Returns the voter's full keys, metadata and key history, for display.
+    pub fn details<'a>(&'a self, voting_key: &'a Address) -> VoterDetails<'a> {
+        VoterDetails {
+            voting_key,
+            stats: self,
+        }
+    }
+}
+
+/// A voter's participation in a subset of the ballots, e.g. the ones of a particular type.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Participation {
+    /// The number of ballots where the voter had a right to vote.
+    pub ballots: usize,
+    /// The number of votes cast by the voter.
+    pub voted: usize,
+}
+
+impl Participation {
+    /// Counts a ballot on which the voter was allowed to vote.
+    pub fn add(&mut self, voted: bool) {
+        self.ballots += 1;
+        if voted {
+            self.voted += 1;
+        }
+    }
+
+    /// Returns the number of ballots the voter missed.
+    pub fn missed(&self) -> usize {
+        self.ballots - self.voted
+    }
+
+    /// Returns the percentage of ballots the voter missed, or `None` if there were none.
+    pub fn missed_percent(&self) -> Option<f64> {
+        if self.ballots == 0 {
+            None
+        } else {
+            Some(self.missed() as f64 * 100.0 / self.ballots as f64)
+        }
+    }
+}
+
+/// The period in which a validator's voting key was active.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Tenure {
+    /// When the validator's first key was added, as a Unix timestamp, if known.
+    pub added: Option<u64>,
+    /// When the validator was removed, as a Unix timestamp, if they were. This is not set if only
+    /// their voting key was replaced.
+    pub removed: Option<u64>,
+}
+
+impl Tenure {
+    /// Records that a key was added at the given time.
+    fn add(&mut self, timestamp: u64) {
+        self.added = Some(self.added.map_or(timestamp, |added| added.min(timestamp)));
+    }
+}
+
+/// A voter's full keys, metadata and key history.
+pub struct VoterDetails<'a> {
+    voting_key: &'a Address,
+    stats: &'a VoterStats,
+}
+
+impl<'a> Display for VoterDetails<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let vs = self.stats;
+        writeln!(f, "{}", vs.name().bold())?;
+        writeln!(f, "  Voting key:     {:?}", self.voting_key)?;
+        if let Some(ref key) = vs.mining_key {
+            writeln!(f, "  Mining key:     {:?}", key)?;
+        }
+        match vs.payout_key {
+            Some(ref key) => writeln!(f, "  Payout key:     {:?}", key)?,
+            None => writeln!(f, "  Payout key:     none")?,
+        }
+        if let Some(ref validator) = vs.validator {
+            validator.fmt(f)?;
+        }
+        if !vs.key_history.is_empty() {
+            writeln!(f, "  Key history:")?;
+            for change in &vs.key_history {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The criteria for flagging a validator as inactive.
+#[derive(Clone, Copy, Debug)]
+pub struct InactivityChecks {
+    /// Flag validators who missed at least this many of the latest ballots in a row.
+    pub ballots: usize,
+    /// Flag validators whose latest vote is older than this.
+    pub period: Duration,
+}
+
+impl Default for InactivityChecks {
+    fn default() -> InactivityChecks {
+        InactivityChecks {
+            ballots: 10,
+            period: Duration::from_secs(90 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// The missed ballot percentages at which a voter's line is highlighted in red or yellow.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColorThresholds {
+    pub red: f32,
+    pub yellow: f32,
+}
+
+impl Default for ColorThresholds {
+    fn default() -> ColorThresholds {
+        ColorThresholds {
+            red: 50.0,
+            yellow: 25.0,
+        }
+    }
+}
+
+/// The ballots created by a validator.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Proposals {
+    /// The number of created ballots in each category, e.g. `add voting key`.
+    pub by_type: BTreeMap<String, usize>,
+    /// The number of created ballots that were accepted.
+    pub accepted: usize,
+    /// The number of created ballots that were rejected.
+    pub rejected: usize,
+    /// The number of created ballots the creator voted on.
+    pub voted_own: usize,
+    /// The number of the creator's ballots that are currently open, according to the contract.
+    pub open: Option<u64>,
+}
+
+impl Proposals {
+    /// Counts a ballot created by the validator with the given voting key.
+    fn add(&mut self, record: &BallotRecord, creator: &Address) {
+        *self.by_type.entry(record.info.description()).or_default() += 1;
+        match record.info.quorum_state {
+            QuorumState::Accepted => self.accepted += 1,
+            QuorumState::Rejected => self.rejected += 1,
+            QuorumState::InProgress | QuorumState::Other(_) => (),
+        }
+        if record.votes.iter().any(|vote| vote.voter == *creator) {
+            self.voted_own += 1;
+        }
+    }
+
+    /// Adds the ballots created with another voting key of the same validator.
+    fn merge(&mut self, other: Proposals) {
+        for (category, n) in other.by_type {
+            *self.by_type.entry(category).or_default() += n;
+        }
+        self.accepted += other.accepted;
+        self.rejected += other.rejected;
+        self.voted_own += other.voted_own;
+    }
+
+    /// Returns the number of created ballots.
+    pub fn total(&self) -> usize {
+        self.by_type.values().sum()
+    }
+
+    /// Returns the share of finalized ballots that were accepted, between 0 and 1, or `None` if
+    /// none were finalized.
+    pub fn success_rate(&self) -> Option<f64> {
+        match self.accepted + self.rejected {
+            0 => None,
+            finalized => Some(self.accepted as f64 / finalized as f64),
+        }
+    }
+}
+
+/// Options that control which voters are displayed, and how.
+#[derive(Clone, Debug)]
+pub struct DisplayOptions {
+    /// Voting or mining keys of validators that should not be listed.
+    pub exclude: BTreeSet<Address>,
+    /// The thresholds for highlighting lines.
+    pub colors: ColorThresholds,
+    /// Whether to list the validators' full metadata below the table.
+    pub details: bool,
+    /// The criteria for license and metadata warnings.
+    pub metadata_checks: MetadataChecks,
+    /// The criteria for flagging validators as inactive.
+    pub inactivity_checks: InactivityChecks,
+    /// The order in which validators are listed.
+    pub sort: SortKey,
+    /// Validators with fewer eligible ballots are not listed.
+    pub min_ballots: usize,
+    /// Whether to list only validators who missed at least one ballot.
+    pub only_missing: bool,
+    /// If set, only validators whose name or keys contain this string are listed.
+    pub filter: Option<String>,
+    /// The columns of the table, in order.
+    pub columns: Vec<Column>,
+    /// The terminal width that the table should fit into, if known.
+    pub width: Option<usize>,
+    /// If set, participation is also listed per week, month or quarter.
+    pub bucket: Option<Bucket>,
+    /// Whether to also list participation per ballot category.
+    pub by_type: bool,
+    /// If set, the agreement between validators is listed, and validators who agreed at least in
+    /// this share of ballots, between 0 and 1, are grouped into blocs.
+    pub agreement: Option<f64>,
+    /// Whether to list the rejected ballots, and the absent validators who could have changed
+    /// the result.
+    pub quorum: bool,
+    /// Whether to list the ballots each validator created.
+    pub creators: bool,
+    /// If set, a recency-weighted participation score is computed, with this half-life.
+    pub half_life: Option<Duration>,
+    /// Validators with fewer eligible ballots are marked as having too few for a reliable share.
+    pub min_sample: usize,
+    /// Whether to list former validators, whose voting key was removed.
+    pub former: bool,
+    /// The windows, ending at the end of the range, in which participation is compared.
+    pub windows: Vec<Window>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> DisplayOptions {
+        DisplayOptions {
+            exclude: BTreeSet::new(),
+            colors: ColorThresholds::default(),
+            details: false,
+            metadata_checks: MetadataChecks::default(),
+            inactivity_checks: InactivityChecks::default(),
+            sort: SortKey::default(),
+            min_ballots: 0,
+            only_missing: false,
+            filter: None,
+            columns: Column::DEFAULT.to_vec(),
+            width: None,
+            bucket: None,
+            by_type: false,
+            agreement: None,
+            quorum: false,
+            creators: false,
+            half_life: None,
+            min_sample: 0,
+            former: false,
+            windows: Vec::new(),
+        }
+    }
+}
+
+/// The range of blocks in which ballots were counted.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct BlockRange {
+    /// The earliest block in which ballots were counted.
+    pub from_block: u64,
+    /// The latest block at the time the ballots were counted.
+    pub to_block: u64,
+    /// The earliest time at which ballots were counted, as a Unix timestamp, if limited.
+    pub from_time: Option<u64>,
+    /// The time before which ballots were counted, as a Unix timestamp, if limited.
+    pub to_time: Option<u64>,
+}
+
+impl BlockRange {
+    /// Returns a sentence describing the period and block range.
+    pub fn description(&self) -> String {
+        // The end time is exclusive: show the last day on which ballots were counted.
+        let to = self
+            .to_time
+            .map_or_else(util::now_timestamp, |time| time.saturating_sub(1));
+        let to = util::format_date(to);
+        let period = match self.from_time {
+            Some(from_time) => format!("from {} to {}", util::format_date(from_time), to),
+            None => format!("until {}", to),
+        };
+        format!(
+            "Ballots created {}, in blocks {} to {}.",
+            period, self.from_block, self.to_block
+        )
+    }
 }
 
 /// A map of vote counts, by voting key.
 #[derive(Clone, Default)]
 pub struct Stats {
     voter_stats: HashMap<Address, VoterStats>,
+    ballots: Vec<BallotRecord>,
+    /// The number of ballots that are currently open for voting.
+    active_ballots: u64,
+    /// The maximum number of open ballots per validator, if known.
+    ballot_limit: Option<u64>,
+    range: BlockRange,
+    options: DisplayOptions,
 }
 
 impl Stats {
-    /// Adds a ballot: `voters` are the voting keys of everyone who was allowed to cast a vote, and
-    /// `votes` are the ones that were actually cast.
-    pub fn add_ballot<'a, I>(&mut self, voters: I, voted: &[Address])
-    where
+    /// Adds a ballot created in the block with the given number and timestamp: `voters` are the
+    /// voting keys of everyone who was allowed to cast a vote, and `votes` are the ones that were
+    /// actually cast.
+    pub fn add_ballot<'a, I>(
+        &mut self,
+        block: u64,
+        timestamp: u64,
+        info: BallotInfo,
+        voters: I,
+        votes: Vec<Vote>,
+    ) where
         I: IntoIterator<Item = &'a Address>,
     {
+        let mut record = BallotRecord {
+            block,
+            timestamp,
+            info,
+            voters: Vec::new(),
+            votes,
+            missed: Vec::new(),
+            reached_threshold: false,
+            votes_short: None,
+        };
+        let category = record.info.description();
+        // Ballots of the other contracts are only counted in the participation by type.
+        let counted = record.info.ballot_type.is_about_keys();
         for voter in voters {
-            let mut vs = self
-                .voter_stats
-                .entry(voter.clone())
-                .or_insert_with(VoterStats::default);
+            let vs = self.voter_stats.entry(*voter).or_default();
+            let vote = record.votes.iter().find(|vote| vote.voter == *voter);
+            vs.by_type
+                .entry(category.clone())
+                .or_default()
+                .add(vote.is_some());
+            record.voters.push(*voter);
+            if vote.is_none() {
+                record.missed.push(*voter);
+            }
+            if !counted {
+                continue;
+            }
             vs.ballots += 1;
-            if voted.contains(voter) {
+            vs.ballot_times.push((timestamp, vote.is_some()));
+            if let Some(vote) = vote {
                 vs.voted += 1;
+                let time_left = record.info.end_time.saturating_sub(vote.time);
+                vs.vote_times.push((vote.latency, time_left));
+                vs.current_streak = 0;
+                vs.last_vote = vs.last_vote.max(Some(vote.time));
+            } else {
+                vs.current_streak += 1;
+                vs.longest_streak = vs.longest_streak.max(vs.current_streak);
+            }
+        }
+        record.update_outcome();
+        let creator = record.info.creator;
+        let vs = self.voter_stats.entry(creator).or_default();
+        vs.created.add(&record, &creator);
+        if counted && record.votes_short.is_some() {
+            let decisive = record.abstainers_decisive();
+            for voter in &record.missed {
+                let vs = self.voter_stats.entry(*voter).or_default();
+                vs.failed_absent += 1;
+                if decisive {
+                    vs.decisive_absent += 1;
+                }
             }
         }
+        self.ballots.push(record);
+    }
+
+    /// Sets the range of blocks in which ballots were counted.
+    pub fn set_range(&mut self, range: BlockRange) {
+        self.range = range;
     }
 
     /// Inserts metadata about a voter: the mining key and the `Validator` information.
@@ -48,79 +557,933 @@ impl Stats {
         vs.validator = Some(validator);
         vs.mining_key = Some(mining_key);
     }
+
+    /// Inserts the voter's payout key, which is zero if there is none, and the history of all
+    /// changes of their keys. The ballots counted for their earlier voting keys are added to
+    /// this one's.
+    pub fn set_keys(&mut self, voter: &Address, payout_key: Address, history: Vec<KeyChange>) {
+        // Ballots counted for an earlier voting key count as the validator's own.
+        let mut earlier = Vec::new();
+        for change in &history {
+            if change.key_type == KeyType::Voting && change.key != *voter {
+                if let Some(old) = self.voter_stats.get_mut(&change.key) {
+                    earlier.push(mem::take(old));
+                }
+            }
+        }
+        let vs = self.voter_stats.entry(*voter).or_default();
+        for old in earlier {
+            vs.merge(old);
+        }
+        vs.payout_key = if payout_key.is_zero() {
+            None
+        } else {
+            Some(payout_key)
+        };
+        for change in &history {
+            if change.action == "added" {
+                vs.tenure.add(change.timestamp);
+            }
+        }
+        vs.key_history = history;
+    }
+
+    /// Records that the voting key was added at the given time.
+    pub fn start_tenure(&mut self, voter: &Address, timestamp: u64) {
+        let vs = self.voter_stats.entry(*voter).or_default();
+        vs.tenure.add(timestamp);
+    }
+
+    /// Records that the validator with the given voting key was removed at the given time.
+    pub fn end_tenure(&mut self, voter: &Address, timestamp: u64) {
+        let vs = self.voter_stats.entry(*voter).or_default();
+        vs.tenure.removed = Some(timestamp);
+    }
+
+    /// Records that the voting key was removed at the given time, either because the validator
+    /// was removed or because the key was replaced. Ballots that were still open at that time,
+    /// and that the voter didn't vote on, are not counted as missed.
+    pub fn remove_voting_key(&mut self, voter: &Address, timestamp: u64) {
+        for record in &mut self.ballots {
+            if record.info.end_time <= timestamp {
+                continue;
+            }
+            let pos = match record.missed.iter().position(|v| v == voter) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let was_decisive = record.abstainers_decisive();
+            record.missed.remove(pos);
+            record.voters.retain(|v| v != voter);
+            let vs = self.voter_stats.entry(*voter).or_default();
+            let category = record.info.description();
+            if let Some(p) = vs.by_type.get_mut(&category) {
+                p.ballots -= 1;
+                if p.ballots == 0 {
+                    vs.by_type.remove(&category);
+                }
+            }
+            if !record.info.ballot_type.is_about_keys() {
+                continue;
+            }
+            vs.ballots -= 1;
+            let entry = (record.timestamp, false);
+            if let Some(i) = vs.ballot_times.iter().rposition(|t| *t == entry) {
+                vs.ballot_times.remove(i);
+            }
+            if record.votes_short.is_some() {
+                vs.failed_absent -= 1;
+                if was_decisive {
+                    vs.decisive_absent -= 1;
+                    // With one abstainer less, the others may not have been able to pass it.
+                    if !record.abstainers_decisive() {
+                        for other in &record.missed {
+                            if let Some(other_vs) = self.voter_stats.get_mut(other) {
+                                other_vs.decisive_absent -= 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(vs) = self.voter_stats.get_mut(voter) {
+            vs.recount_streaks();
+        }
+    }
+
+    /// Returns the voting keys of all voters whose mining key is not known yet: they belong to
+    /// former validators, or were replaced by a new voting key.
+    pub fn former_voters(&self) -> Vec<Address> {
+        self.voter_stats
+            .iter()
+            .filter(|(_, vs)| vs.mining_key.is_none())
+            .map(|(voter, _)| *voter)
+            .collect()
+    }
+
+    /// Sets the number of currently open ballots created by the voter.
+    pub fn set_open_ballots(&mut self, voter: &Address, open: u64) {
+        self.voter_stats.entry(*voter).or_default().created.open = Some(open);
+    }
+
+    /// Sets the maximum number of open ballots per validator.
+    pub fn set_ballot_limit(&mut self, ballot_limit: u64) {
+        self.ballot_limit = Some(ballot_limit);
+    }
+
+    /// Returns the maximum number of open ballots per validator, if known.
+    pub fn ballot_limit(&self) -> Option<u64> {
+        self.ballot_limit
+    }
+
+    /// Sets the options that control which voters are displayed, and how.
+    pub fn set_display_options(&mut self, options: DisplayOptions) {
+        self.options = options;
+    }
+
+    /// Returns `true` if the voter with the given voting key should not be listed.
+    fn is_excluded(&self, voter: &Address, vs: &VoterStats) -> bool {
+        let options = &self.options;
+        let exclude = &options.exclude;
+        exclude.contains(voter)
+            || vs.mining_key.map_or(false, |key| exclude.contains(&key))
+            || vs.ballots < options.min_ballots
+            || (options.only_missing && vs.missed() == 0)
+            || (!options.former && vs.tenure.removed.is_some())
+    }
+
+    /// Returns the voting keys and statistics of all voters that should be listed, in the same
+    /// order as in the table.
+    pub fn listed(&self) -> Vec<(&Address, &VoterStats)> {
+        self.display_lines()
+            .iter()
+            .filter_map(|line| self.voter_stats.get_key_value(&line.voting_address))
+            .collect()
+    }
+
+    /// Returns the voting keys of all voters whose voting or mining key is `query`, or who used
+    /// `query` as an earlier voting key. If there are none, it returns the voters whose name is
+    /// `query`, or else contains it, ignoring case.
+    pub fn find(&self, query: &str) -> Vec<&Address> {
+        if let Ok(key) = query.trim_start_matches("0x").parse::<Address>() {
+            let found: Vec<&Address> = self
+                .voter_stats
+                .iter()
+                .filter(|(voter, vs)| {
+                    vs.mining_key.is_some()
+                        && (**voter == key
+                            || vs.mining_key == Some(key)
+                            || vs.key_history.iter().any(|change| change.key == key))
+                })
+                .map(|(voter, _)| voter)
+                .collect();
+            if found.is_empty() {
+                return self
+                    .voter_stats
+                    .get_key_value(&key)
+                    .into_iter()
+                    .map(|(voter, _)| voter)
+                    .collect();
+            }
+            return found;
+        }
+        let query = query.to_lowercase();
+        let find_names = |exact: bool| -> Vec<&Address> {
+            self.voter_stats
+                .iter()
+                .filter(|(_, vs)| {
+                    let name = vs.name().to_lowercase();
+                    (exact && name == query) || (!exact && name.contains(&query))
+                })
+                .map(|(voter, _)| voter)
+                .collect()
+        };
+        let exact = find_names(true);
+        if exact.is_empty() {
+            find_names(false)
+        } else {
+            exact
+        }
+    }
+
+    /// Returns the statistics for the voter with the given voting key.
+    pub fn get(&self, voter: &Address) -> Option<&VoterStats> {
+        self.voter_stats.get(voter)
+    }
+
+    /// Sets the number of ballots that are currently open for voting.
+    pub fn set_active_ballots(&mut self, active_ballots: u64) {
+        self.active_ballots = active_ballots;
+    }
+
+    /// Returns the number of ballots that are currently open for voting.
+    pub fn active_ballots(&self) -> u64 {
+        self.active_ballots
+    }
+
+    /// Returns the options that control which voters are displayed, and how.
+    pub fn display_options(&self) -> &DisplayOptions {
+        &self.options
+    }
+
+    /// Returns the range of blocks in which ballots were counted.
+    pub fn range(&self) -> &BlockRange {
+        &self.range
+    }
+
+    /// Returns the names of the periods into which ballots are grouped, if any, in order.
+    pub fn period_labels(&self) -> Vec<String> {
+        self.options
+            .bucket
+            .map_or_else(Vec::new, |bucket| period::labels(&self.ballots, bucket))
+    }
+
+    /// Returns the voter's participation in each period, if ballots are grouped by time.
+    pub fn periods(&self, voter: &Address) -> Option<BTreeMap<String, Participation>> {
+        let bucket = self.options.bucket?;
+        let keys = self.voting_keys(voter);
+        Some(period::participation(&self.ballots, &keys, bucket))
+    }
+
+    /// Returns the labels of the windows in which participation is compared, in order.
+    pub fn window_labels(&self) -> Vec<String> {
+        let windows = &self.options.windows;
+        windows.iter().map(|window| window.label.clone()).collect()
+    }
+
+    /// Returns the voter's participation in each window, if windows are compared.
+    pub fn windows(&self, voter: &Address) -> Option<BTreeMap<String, Participation>> {
+        if self.options.windows.is_empty() {
+            return None;
+        }
+        let end = self.range.to_time.unwrap_or_else(util::now_timestamp);
+        let windows = &self.options.windows;
+        let keys = self.voting_keys(voter);
+        Some(period::window_participation(
+            &self.ballots,
+            &keys,
+            windows,
+            end,
+        ))
+    }
+
+    /// Returns the voter's current voting key and all their earlier ones, whose ballots are
+    /// counted together.
+    pub fn voting_keys(&self, voter: &Address) -> Vec<Address> {
+        let mut keys = vec![*voter];
+        if let Some(vs) = self.voter_stats.get(voter) {
+            let history = vs.key_history.iter();
+            keys.extend(
+                history
+                    .filter(|change| change.key_type == KeyType::Voting && change.key != *voter)
+                    .map(|change| change.key),
+            );
+        }
+        keys
+    }
+
+    /// Returns the categories of all counted ballots, e.g. `add voting key`, in alphabetical order.
+    pub fn categories(&self) -> Vec<String> {
+        let categories: BTreeSet<String> = self
+            .ballots
+            .iter()
+            .map(|record| record.info.description())
+            .collect();
+        categories.into_iter().collect()
+    }
+
+    /// Returns the agreement between the listed validators, if enabled in the options.
+    pub fn agreement(&self) -> Option<AgreementMatrix> {
+        let threshold = self.options.agreement?;
+        let voters = self.listed().into_iter().map(|(voter, _)| *voter).collect();
+        Some(AgreementMatrix::new(voters, &self.ballots, threshold))
+    }
+
+    /// Returns all counted ballots, in chronological order.
+    pub fn ballots(&self) -> &[BallotRecord] {
+        &self.ballots
+    }
+
+    /// Returns the display lines of all voters that should be listed, in order.
+    fn display_lines(&self) -> Vec<DisplayLine> {
+        let mut lines: Vec<_> = self
+            .voter_stats
+            .iter()
+            .filter(|(addr, vs)| !self.is_excluded(addr, vs))
+            .filter_map(|entry| to_display_line(entry, &self.options, self.ballot_limit))
+            .collect();
+        if let Some(ref filter) = self.options.filter {
+            let filter = filter.to_lowercase();
+            lines.retain(|line| line.matches(&filter));
+        }
+        let sort = self.options.sort;
+        lines.sort_by(|l0, l1| l0.compare(l1, sort));
+        lines
+    }
+
+    /// Returns warnings about the voter's keys, license and metadata.
+    fn warnings(&self, voter: &Address, vs: &VoterStats, now: SystemTime) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if vs.payout_key.is_none() {
+            warnings.push("No payout key".to_string());
+        }
+        if vs.mining_key == Some(*voter) {
+            warnings.push("Voting key is the same as the mining key".to_string());
+        }
+        let now_timestamp = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        if let Some(inactivity) = vs.inactivity(now_timestamp, &self.options.inactivity_checks) {
+            warnings.push(format!("Inactive: {}", inactivity));
+        }
+        if let (Some(open), Some(limit)) = (vs.created.open, self.ballot_limit) {
+            if open >= limit {
+                warnings.push(format!("Reached the limit of {} open ballots", limit));
+            }
+        }
+        if let Some(ref validator) = vs.validator {
+            warnings.extend(validator.warnings(now, &self.options.metadata_checks));
+        }
+        warnings
+    }
+
+    /// Lists the voting records of all validators who are inactive, or have problematic keys, an
+    /// expired or expiring license, or outdated metadata.
+    fn fmt_warnings(&self, f: &mut Formatter, lines: &[DisplayLine], table: &Table) -> fmt::Result {
+        let now = SystemTime::now();
+        let mut header_written = false;
+        for (i, line) in lines.iter().enumerate() {
+            let vs = &self.voter_stats[&line.voting_address];
+            if vs.tenure.removed.is_some() {
+                continue; // Former validators can't fix anything anymore.
+            }
+            let warnings = self.warnings(&line.voting_address, vs, now);
+            if warnings.is_empty() {
+                continue;
+            }
+            if !header_written {
+                writeln!(f, "\n{}", "Warnings".bold())?;
+                header_written = true;
+            }
+            writeln!(f, "{}", table.line(i).color(line.color()))?;
+            for warning in warnings {
+                writeln!(f, "         {}", warning.bright_red())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the full keys, key history and metadata of every listed validator.
+    fn fmt_details(&self, f: &mut Formatter, lines: &[DisplayLine]) -> fmt::Result {
+        writeln!(f, "\n{}", "Validator details".bold())?;
+        for line in lines {
+            let vs = &self.voter_stats[&line.voting_address];
+            vs.details(&line.voting_address).fmt(f)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the number of missed and eligible ballots of every listed validator in each period.
+    fn fmt_periods(&self, f: &mut Formatter, lines: &[DisplayLine], bucket: Bucket) -> fmt::Result {
+        let title = format!("Missed ballots by {}", bucket.name());
+        let rows = lines
+            .iter()
+            .map(|line| self.periods(&line.voting_address).unwrap_or_default())
+            .collect();
+        self.fmt_matrix(f, &title, lines, &self.period_labels(), rows, false)
+    }
+
+    /// Lists the number of missed and eligible ballots of every listed validator in each ballot
+    /// category.
+    fn fmt_by_type(&self, f: &mut Formatter, lines: &[DisplayLine]) -> fmt::Result {
+        let rows = lines
+            .iter()
+            .map(|line| self.voter_stats[&line.voting_address].by_type.clone())
+            .collect();
+        let categories = self.categories();
+        self.fmt_matrix(f, "Missed ballots by type", lines, &categories, rows, false)
+    }
+
+    /// Lists the number of missed and eligible ballots of every listed validator in each window,
+    /// with trend arrows.
+    fn fmt_windows(&self, f: &mut Formatter, lines: &[DisplayLine]) -> fmt::Result {
+        let rows = lines
+            .iter()
+            .map(|line| self.windows(&line.voting_address).unwrap_or_default())
+            .collect();
+        let labels = self.window_labels();
+        self.fmt_matrix(f, "Missed ballots by window", lines, &labels, rows, true)
+    }
+
+    /// Writes a table with a row for each line and a column for each label, showing the missed
+    /// and eligible ballots. With `trend`, the cells also show the percentage, and an arrow
+    /// comparing it with the next column.
+    fn fmt_matrix(
+        &self,
+        f: &mut Formatter,
+        title: &str,
+        lines: &[DisplayLine],
+        labels: &[String],
+        rows: Vec<BTreeMap<String, Participation>>,
+        trend: bool,
+    ) -> fmt::Result {
+        writeln!(f, "\n{}", title.bold())?;
+        let cell = |row: &BTreeMap<String, Participation>, i: usize| match row.get(&labels[i]) {
+            Some(p) if trend => {
+                let percent = p
+                    .missed_percent()
+                    .map_or_else(|| "-".to_string(), |percent| format!("{:.0}%", percent));
+                let text = format!("{}/{} {}", p.missed(), p.ballots, percent);
+                let next = labels.get(i + 1).and_then(|label| row.get(label));
+                match next.and_then(|next| trend_arrow(p, next)) {
+                    Some(arrow) => format!("{} {}", text, arrow),
+                    None => text,
+                }
+            }
+            Some(p) => format!("{}/{}", p.missed(), p.ballots),
+            None => "-".to_string(),
+        };
+        let name_width = lines
+            .iter()
+            .map(|line| line.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_MATRIX_NAME_WIDTH);
+        let widths: Vec<usize> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let cells = rows.iter().map(|row| cell(row, i).chars().count());
+                cells.chain(Some(label.chars().count())).max().unwrap_or(0)
+            })
+            .collect();
+        write!(f, "{}", format!("{:1$}", "Name", name_width).bold())?;
+        for (label, width) in labels.iter().zip(&widths) {
+            write!(f, "  {}", format!("{:>1$}", label, width).bold())?;
+        }
+        writeln!(f)?;
+        for (line, row) in lines.iter().zip(&rows) {
+            let name: String = line.name.chars().take(name_width).collect();
+            write!(f, "{:1$}", name, name_width)?;
+            for (i, (label, width)) in labels.iter().zip(&widths).enumerate() {
+                let text = format!("{:>1$}", cell(row, i), width);
+                match row.get(label).and_then(|p| Some((p, p.missed_percent()?))) {
+                    Some((p, percent)) => {
+                        let color = missed_color(percent as f32, p.missed(), line.colors);
+                        write!(f, "  {}", text.color(color))?;
+                    }
+                    None => write!(f, "  {}", text)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the rejected ballots, with the number of votes they fell short by, and the absent
+    /// validators.
+    fn fmt_rejected(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "\n{}", "Rejected ballots".bold())?;
+        let mut any = false;
+        for record in &self.ballots {
+            let short = match record.votes_short {
+                Some(short) => short,
+                None => continue,
+            };
+            any = true;
+            let info = &record.info;
+            writeln!(
+                f,
+                "Ballot {}: {} {}",
+                info.id,
+                info.description(),
+                info.subject()
+            )?;
+            let threshold = if record.reached_threshold {
+                "reached"
+            } else {
+                "not reached"
+            };
+            writeln!(
+                f,
+                "  {} accepted, {} rejected, threshold {} {}, {} absent.",
+                record.count(Decision::Accept),
+                record.count(Decision::Reject),
+                info.min_threshold,
+                threshold,
+                record.missed.len()
+            )?;
+            if record.abstainers_decisive() {
+                let names: Vec<String> = record
+                    .missed
+                    .iter()
+                    .map(|voter| self.voter_stats[voter].name())
+                    .collect();
+                let text = if short == 1 {
+                    "Any one of the absent validators could have passed it".to_string()
+                } else {
+                    format!("{} of the absent validators could have passed it", short)
+                };
+                writeln!(f, "  {}: {}", text.bright_red(), names.join(", "))?;
+            } else if short > 0 {
+                writeln!(f, "  {} more accepting votes were needed.", short)?;
+            }
+        }
+        if !any {
+            writeln!(f, "None.")?;
+        }
+        Ok(())
+    }
+
+    /// Lists the number of ballots each listed validator created, by type, and their outcomes.
+    fn fmt_creators(&self, f: &mut Formatter, lines: &[DisplayLine]) -> fmt::Result {
+        writeln!(f, "\n{}", "Ballot creators".bold())?;
+        let mut any = false;
+        for line in lines {
+            let created = &line.created;
+            let total = created.total();
+            if total == 0 && created.open.unwrap_or(0) == 0 {
+                continue;
+            }
+            any = true;
+            let types: Vec<String> = created
+                .by_type
+                .iter()
+                .map(|(category, n)| format!("{} {}", n, category))
+                .collect();
+            writeln!(f, "{}: {} ballots ({})", line.name, total, types.join(", "))?;
+            write!(
+                f,
+                "  {} accepted, {} rejected",
+                created.accepted, created.rejected
+            )?;
+            if let Some(rate) = created.success_rate() {
+                write!(f, " ({:.0}% success)", rate * 100.0)?;
+            }
+            write!(f, ", voted on {} of them", created.voted_own)?;
+            match (created.open, self.ballot_limit) {
+                (Some(open), Some(limit)) => write!(f, ", {} of at most {} open", open, limit)?,
+                (Some(open), None) => write!(f, ", {} open", open)?,
+                (None, _) => (),
+            }
+            writeln!(f, ".")?;
+        }
+        if !any {
+            writeln!(f, "None.")?;
+        }
+        Ok(())
+    }
+}
+
+/// A voter's statistics, together with their voting key, for serialization.
+#[derive(Serialize)]
+struct VoterEntry<'a> {
+    voting_key: &'a Address,
+    missed: usize,
+    #[serde(flatten)]
+    stats: &'a VoterStats,
+    /// The participation in each period, if grouped by time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    periods: Option<BTreeMap<String, Participation>>,
+    /// The participation in each window, if windows are compared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows: Option<BTreeMap<String, Participation>>,
+    latency: Option<Latency>,
+    /// The recency-weighted participation score, if enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    /// The 95% confidence interval of the share of missed ballots.
+    missed_interval: Option<Interval>,
+    /// Whether there are too few eligible ballots for a reliable share.
+    few_ballots: bool,
+    /// The reason why the voter is considered inactive, if they are.
+    inactive: Option<String>,
+}
+
+/// Serializes the listed voters, in the same order as the table, and all ballots.
+impl Serialize for Stats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let now = util::now_timestamp();
+        let voters: Vec<VoterEntry> = self
+            .listed()
+            .into_iter()
+            .map(|(voting_key, stats)| VoterEntry {
+                voting_key,
+                missed: stats.missed(),
+                stats,
+                periods: self.periods(voting_key),
+                windows: self.windows(voting_key),
+                latency: stats.latency(),
+                score: self
+                    .options
+                    .half_life
+                    .and_then(|half_life| stats.score(now, half_life)),
+                missed_interval: stats.missed_interval(),
+                few_ballots: stats.ballots < self.options.min_sample,
+                inactive: stats.inactivity(now, &self.options.inactivity_checks),
+            })
+            .collect();
+        let agreement = self.agreement();
+        let mut state = serializer.serialize_struct("Stats", 6)?;
+        state.serialize_field("range", &self.range)?;
+        state.serialize_field("active_ballots", &self.active_ballots)?;
+        state.serialize_field("ballot_limit", &self.ballot_limit)?;
+        state.serialize_field("validators", &voters)?;
+        state.serialize_field("ballots", &self.ballots)?;
+        if let Some(ref agreement) = agreement {
+            state.serialize_field("agreement", agreement)?;
+        } else {
+            state.skip_field("agreement")?;
+        }
+        state.end()
+    }
 }
 
-fn to_display_line((addr, s): (&Address, &VoterStats)) -> Option<DisplayLine> {
+fn to_display_line(
+    (addr, s): (&Address, &VoterStats),
+    options: &DisplayOptions,
+    ballot_limit: Option<u64>,
+) -> Option<DisplayLine> {
     let votes_per_thousand = if s.ballots == 0 {
-        1000
+        None
     } else {
-        s.voted * 1000 / s.ballots
-    };
-    let mining_key = match s.mining_key {
-        None => return None,
-        Some(ref key) => format!("{}", key),
+        Some(s.voted * 1000 / s.ballots)
     };
+    let mining_key = s.mining_key?;
     let name = match s.validator {
         None => return None,
-        Some(ref v) => format!("{} {}", v.first_name, v.last_name),
+        Some(ref v) => v.name(),
     };
+    let now = util::now_timestamp();
     Some(DisplayLine {
+        colors: options.colors,
         votes_per_thousand,
         voted: s.voted,
         ballots: s.ballots,
         voting_address: *addr,
         mining_key,
+        payout_key: s.payout_key,
         name,
+        latency: s.latency(),
+        current_streak: s.current_streak,
+        longest_streak: s.longest_streak,
+        last_vote: s.last_vote,
+        failed_absent: s.failed_absent,
+        decisive_absent: s.decisive_absent,
+        created: s.created.clone(),
+        ballot_limit,
+        score: options
+            .half_life
+            .and_then(|half_life| s.score(now, half_life)),
+        interval: s.missed_interval(),
+        tenure: s.tenure,
+        few_ballots: s.ballots < options.min_sample,
     })
 }
 
 impl Display for Stats {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let mut lines: Vec<_> = self
-            .voter_stats
-            .iter()
-            .filter_map(to_display_line)
-            .collect();
-        lines.sort();
-        let header = "        Missed  Voting key   Mining key   Name".bold();
-        writeln!(f, "{}", header)?;
-        for line in lines {
-            line.fmt(f)?;
+        let lines = self.display_lines();
+        let cells = |columns: &[Column]| lines.iter().map(|line| line.cells(columns)).collect();
+        let table = Table::new(&self.options.columns, cells, self.options.width);
+        writeln!(f, "{}", table.header().bold())?;
+        for (i, line) in lines.iter().enumerate() {
+            writeln!(f, "{}", table.line(i).color(line.color()))?;
+        }
+        let marked = [Column::Missed, Column::Percent, Column::Interval];
+        if lines.iter().any(|line| line.few_ballots)
+            && self.options.columns.iter().any(|c| marked.contains(c))
+        {
+            let n = self.options.min_sample;
+            writeln!(
+                f,
+                "* Fewer than {} eligible ballots: the share is unreliable.",
+                n
+            )?;
+        }
+        if let Some(bucket) = self.options.bucket {
+            self.fmt_periods(f, &lines, bucket)?;
+        }
+        if !self.options.windows.is_empty() {
+            self.fmt_windows(f, &lines)?;
+        }
+        if self.options.by_type {
+            self.fmt_by_type(f, &lines)?;
+        }
+        if let Some(agreement) = self.agreement() {
+            let names: Vec<String> = lines.iter().map(|line| line.name.clone()).collect();
+            agreement.fmt_table(f, &names)?;
+        }
+        if self.options.quorum {
+            self.fmt_rejected(f)?;
+        }
+        if self.options.creators {
+            self.fmt_creators(f, &lines)?;
+        }
+        self.fmt_warnings(f, &lines, &table)?;
+        if self.options.details {
+            self.fmt_details(f, &lines)?;
         }
         Ok(())
     }
 }
 
 /// A line in the output, corresponding to a particular voter.
-#[derive(Ord, PartialOrd, Eq, PartialEq)]
 struct DisplayLine {
-    votes_per_thousand: usize,
+    colors: ColorThresholds,
+    /// The share of eligible ballots the voter voted on, in thousandths, or `None` if there were
+    /// none.
+    votes_per_thousand: Option<usize>,
     voted: usize,
     ballots: usize,
     voting_address: Address,
-    mining_key: String,
+    mining_key: Address,
+    payout_key: Option<Address>,
     name: String,
+    latency: Option<Latency>,
+    current_streak: usize,
+    longest_streak: usize,
+    last_vote: Option<u64>,
+    failed_absent: usize,
+    decisive_absent: usize,
+    created: Proposals,
+    ballot_limit: Option<u64>,
+    score: Option<f64>,
+    interval: Option<Interval>,
+    tenure: Tenure,
+    /// Whether there are too few eligible ballots for a reliable share.
+    few_ballots: bool,
 }
 
-impl Display for DisplayLine {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let text = format!(
-            "{:>7},{:5.1}%  {}  {}  {}",
-            format!("{}/{}", self.ballots - self.voted, self.ballots),
-            100.0 - (self.votes_per_thousand as f32) / 10.0,
-            self.voting_address,
-            self.mining_key,
-            self.name
-        );
-        let c = if self.votes_per_thousand <= 500 {
-            Color::BrightRed
-        } else if self.votes_per_thousand <= 750 {
-            Color::BrightYellow
-        } else if self.votes_per_thousand < 1000 {
-            Color::White
-        } else {
-            Color::BrightGreen
+impl DisplayLine {
+    /// Returns the values by which lines are sorted.
+    fn sort_key(
+        &self,
+    ) -> (
+        Option<usize>,
+        usize,
+        usize,
+        &Address,
+        &Address,
+        &Option<Address>,
+        &str,
+    ) {
+        (
+            self.votes_per_thousand,
+            self.voted,
+            self.ballots,
+            &self.voting_address,
+            &self.mining_key,
+            &self.payout_key,
+            &self.name,
+        )
+    }
+
+    /// Compares two lines by the given sort key, and by the default order if that is equal.
+    fn compare(&self, other: &DisplayLine, sort: SortKey) -> Ordering {
+        let ordering = match sort {
+            SortKey::Missed => Ordering::Equal,
+            SortKey::Name => self.name.to_lowercase().cmp(&other.name.to_lowercase()),
+            SortKey::Ballots => other.ballots.cmp(&self.ballots),
+            SortKey::MiningKey => self.mining_key.cmp(&other.mining_key),
+            // The lowest score first. Validators without any ballots come first in each order:
+            // there is no evidence that they participate at all.
+            SortKey::Score => compare_none_first(self.score, other.score),
+            // The highest upper bound first, i.e. the lowest lower bound of the share of votes.
+            SortKey::Wilson => compare_none_first(
+                self.interval.map(|i| -i.upper),
+                other.interval.map(|i| -i.upper),
+            ),
+        };
+        ordering.then_with(|| self.sort_key().cmp(&other.sort_key()))
+    }
+
+    /// Returns `true` if the name or any of the keys contains the given lowercase string.
+    fn matches(&self, filter: &str) -> bool {
+        let keys = [
+            Some(self.voting_address),
+            Some(self.mining_key),
+            self.payout_key,
+        ];
+        self.name.to_lowercase().contains(filter)
+            || keys
+                .iter()
+                .filter_map(|key| key.as_ref())
+                .any(|key| format!("{:?}", key).contains(filter))
+    }
+
+    /// Returns the percentage of ballots this voter missed, or `None` if there were no ballots.
+    fn missed_percent(&self) -> Option<f32> {
+        self.votes_per_thousand
+            .map(|votes| 100.0 - (votes as f32) / 10.0)
+    }
+
+    /// Returns the text in each of the given columns.
+    fn cells(&self, columns: &[Column]) -> Vec<String> {
+        let payout = |full: bool| match self.payout_key {
+            None => "none".to_string(),
+            Some(ref key) if full => format!("{:?}", key),
+            Some(ref key) => format!("{}", key),
         };
-        writeln!(f, "{}", text.color(c))
+        columns
+            .iter()
+            .map(|column| match *column {
+                Column::Missed => {
+                    self.mark(format!("{}/{}", self.ballots - self.voted, self.ballots))
+                }
+                Column::Percent => self
+                    .missed_percent()
+                    .map_or_else(|| "-".to_string(), |p| self.mark(format!("{:.1}%", p))),
+                Column::Interval => self
+                    .interval
+                    .map_or_else(|| "-".to_string(), |i| self.mark(i.to_string())),
+                Column::VotingKey => format!("{}", self.voting_address),
+                Column::MiningKey => format!("{}", self.mining_key),
+                Column::PayoutKey => payout(false),
+                Column::VotingAddress => format!("{:?}", self.voting_address),
+                Column::MiningAddress => format!("{:?}", self.mining_key),
+                Column::PayoutAddress => payout(true),
+                Column::Name => self.name.clone(),
+                Column::MedianLatency => self
+                    .latency
+                    .map_or_else(|| "-".to_string(), |l| latency::format_duration(l.median)),
+                Column::P90Latency => self
+                    .latency
+                    .map_or_else(|| "-".to_string(), |l| latency::format_duration(l.p90)),
+                Column::Streak => self.current_streak.to_string(),
+                Column::LongestStreak => self.longest_streak.to_string(),
+                Column::LastVote => self
+                    .last_vote
+                    .map_or_else(|| "-".to_string(), util::format_date),
+                Column::FailedAbsent => self.failed_absent.to_string(),
+                Column::DecisiveAbsent => self.decisive_absent.to_string(),
+                Column::Score => self
+                    .score
+                    .map_or_else(|| "-".to_string(), |score| format!("{:.1}%", score)),
+                Column::Added => self
+                    .tenure
+                    .added
+                    .map_or_else(|| "-".to_string(), util::format_date),
+                Column::Removed => self
+                    .tenure
+                    .removed
+                    .map_or_else(|| "-".to_string(), util::format_date),
+                Column::Created => self.created.total().to_string(),
+                Column::Success => self
+                    .created
+                    .success_rate()
+                    .map_or_else(|| "-".to_string(), |rate| format!("{:.0}%", rate * 100.0)),
+                Column::OwnVotes => {
+                    format!("{}/{}", self.created.voted_own, self.created.total())
+                }
+                Column::OpenBallots => match (self.created.open, self.ballot_limit) {
+                    (Some(open), Some(limit)) => format!("{}/{}", open, limit),
+                    (Some(open), None) => open.to_string(),
+                    (None, _) => "-".to_string(),
+                },
+                Column::LastDay => self.latency.map_or_else(
+                    || "-".to_string(),
+                    |l| format!("{:.0}%", l.last_day_share * 100.0),
+                ),
+            })
+            .collect()
+    }
+
+    /// Appends an asterisk to the text if there are too few ballots for a reliable share.
+    fn mark(&self, text: String) -> String {
+        if self.few_ballots {
+            text + "*"
+        } else {
+            text
+        }
+    }
+
+    /// Returns the color in which the line is highlighted.
+    fn color(&self) -> Color {
+        match self.missed_percent() {
+            Some(missed_percent) => {
+                missed_color(missed_percent, self.ballots - self.voted, self.colors)
+            }
+            None => Color::White,
+        }
+    }
+}
+
+/// Returns the color for the given percentage and number of missed ballots.
+fn missed_color(missed_percent: f32, missed: usize, colors: ColorThresholds) -> Color {
+    if missed_percent >= colors.red {
+        Color::BrightRed
+    } else if missed_percent >= colors.yellow {
+        Color::BrightYellow
+    } else if missed > 0 {
+        Color::White
+    } else {
+        Color::BrightGreen
+    }
+}
+
+/// The difference in percentage points below which the share of missed ballots is considered
+/// unchanged.
+const TREND_TOLERANCE: f64 = 1.0;
+
+/// Returns an arrow that shows whether the share of missed ballots in the `recent` window is
+/// lower (`↑`, better), higher (`↓`, worse) or about the same (`→`) as in the `longer` one.
+/// Returns `None` if either has no ballots.
+fn trend_arrow(recent: &Participation, longer: &Participation) -> Option<&'static str> {
+    let diff = longer.missed_percent()? - recent.missed_percent()?;
+    Some(if diff > TREND_TOLERANCE {
+        "↑"
+    } else if diff < -TREND_TOLERANCE {
+        "↓"
+    } else {
+        "→"
+    })
+}
+
+/// Compares two optional values, with `None` before all others.
+fn compare_none_first(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
     }
 }
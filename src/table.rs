@@ -0,0 +1,309 @@
+use crate::error::{Error, ErrorKind};
+use std::str::FromStr;
+
+/// The order in which validators are listed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortKey {
+    /// The highest share of missed ballots first.
+    #[default]
+    Missed,
+    /// Alphabetically by name.
+    Name,
+    /// The most ballots first.
+    Ballots,
+    /// By mining key.
+    MiningKey,
+    /// The lowest recency-weighted participation score first.
+    Score,
+    /// The highest upper bound of the confidence interval of the share of missed ballots first.
+    Wilson,
+}
+
+impl SortKey {
+    /// The names of all sort keys.
+    pub const NAMES: &'static [&'static str] =
+        &["missed", "name", "ballots", "mining-key", "score", "wilson"];
+}
+
+impl FromStr for SortKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SortKey, Error> {
+        match s {
+            "missed" => Ok(SortKey::Missed),
+            "name" => Ok(SortKey::Name),
+            "ballots" => Ok(SortKey::Ballots),
+            "mining-key" => Ok(SortKey::MiningKey),
+            "score" => Ok(SortKey::Score),
+            "wilson" => Ok(SortKey::Wilson),
+            _ => Err(ErrorKind::UnknownSortKey(s.to_string()).into()),
+        }
+    }
+}
+
+/// A column of the validator table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Column {
+    /// The number of missed ballots, out of the number of eligible ballots.
+    Missed,
+    /// The percentage of missed ballots.
+    Percent,
+    /// The 95% confidence interval of the share of missed ballots.
+    Interval,
+    /// The recency-weighted percentage of ballots the validator voted on.
+    Score,
+    /// The abbreviated voting key.
+    VotingKey,
+    /// The abbreviated mining key.
+    MiningKey,
+    /// The abbreviated payout key.
+    PayoutKey,
+    /// The full voting key.
+    VotingAddress,
+    /// The full mining key.
+    MiningAddress,
+    /// The full payout key.
+    PayoutAddress,
+    /// The validator's name.
+    Name,
+    /// The median time from the start of a ballot until the validator's vote.
+    MedianLatency,
+    /// The 90th percentile of the time from the start of a ballot until the validator's vote.
+    P90Latency,
+    /// The share of votes cast in the last 24 hours before the end of the ballot.
+    LastDay,
+    /// The number of consecutive missed ballots, up to the latest one.
+    Streak,
+    /// The longest run of consecutive missed ballots.
+    LongestStreak,
+    /// The date of the validator's latest vote.
+    LastVote,
+    /// The date the validator's first key was added.
+    Added,
+    /// The date the validator was removed.
+    Removed,
+    /// The number of rejected ballots the validator missed.
+    FailedAbsent,
+    /// The number of rejected ballots the validator missed, where the absent validators could
+    /// have changed the result.
+    DecisiveAbsent,
+    /// The number of ballots the validator created.
+    Created,
+    /// The share of the validator's finalized ballots that were accepted.
+    Success,
+    /// The number of the validator's own ballots they voted on.
+    OwnVotes,
+    /// The number of the validator's currently open ballots, and the limit.
+    OpenBallots,
+}
+
+impl Column {
+    /// The columns that are shown if none are selected explicitly.
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Missed,
+        Column::Percent,
+        Column::VotingKey,
+        Column::MiningKey,
+        Column::PayoutKey,
+        Column::Name,
+    ];
+
+    /// Parses a comma-separated list of column names.
+    pub fn parse_list(s: &str) -> Result<Vec<Column>, Error> {
+        s.split(',').map(|name| name.trim().parse()).collect()
+    }
+
+    /// Returns the column's title in the table header.
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Missed => "Missed",
+            Column::Percent => "%",
+            Column::Interval => "95% CI",
+            Column::Score => "Score",
+            Column::VotingKey => "Voting key",
+            Column::MiningKey => "Mining key",
+            Column::PayoutKey => "Payout key",
+            Column::VotingAddress => "Voting key",
+            Column::MiningAddress => "Mining key",
+            Column::PayoutAddress => "Payout key",
+            Column::Name => "Name",
+            Column::MedianLatency => "Median",
+            Column::P90Latency => "90th pct.",
+            Column::LastDay => "Last 24h",
+            Column::Streak => "Streak",
+            Column::LongestStreak => "Longest",
+            Column::LastVote => "Last vote",
+            Column::Added => "Added",
+            Column::Removed => "Removed",
+            Column::FailedAbsent => "Failed",
+            Column::DecisiveAbsent => "Decisive",
+            Column::Created => "Created",
+            Column::Success => "Success",
+            Column::OwnVotes => "Own votes",
+            Column::OpenBallots => "Open",
+        }
+    }
+
+    /// Returns the column that shows the same key abbreviated, or the column itself.
+    pub fn abbreviated(self) -> Column {
+        match self {
+            Column::VotingAddress => Column::VotingKey,
+            Column::MiningAddress => Column::MiningKey,
+            Column::PayoutAddress => Column::PayoutKey,
+            column => column,
+        }
+    }
+
+    /// Returns `true` if the column's values are right-aligned.
+    pub fn is_numeric(self) -> bool {
+        !matches!(
+            self,
+            Column::VotingKey
+                | Column::MiningKey
+                | Column::PayoutKey
+                | Column::VotingAddress
+                | Column::MiningAddress
+                | Column::PayoutAddress
+                | Column::Name
+        )
+    }
+}
+
+impl FromStr for Column {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Column, Error> {
+        match s {
+            "missed" => Ok(Column::Missed),
+            "percent" => Ok(Column::Percent),
+            "interval" => Ok(Column::Interval),
+            "score" => Ok(Column::Score),
+            "voting-key" => Ok(Column::VotingKey),
+            "mining-key" => Ok(Column::MiningKey),
+            "payout-key" => Ok(Column::PayoutKey),
+            "voting-address" => Ok(Column::VotingAddress),
+            "mining-address" => Ok(Column::MiningAddress),
+            "payout-address" => Ok(Column::PayoutAddress),
+            "name" => Ok(Column::Name),
+            "median-latency" => Ok(Column::MedianLatency),
+            "p90-latency" => Ok(Column::P90Latency),
+            "last-day" => Ok(Column::LastDay),
+            "streak" => Ok(Column::Streak),
+            "longest-streak" => Ok(Column::LongestStreak),
+            "last-vote" => Ok(Column::LastVote),
+            "added" => Ok(Column::Added),
+            "removed" => Ok(Column::Removed),
+            "failed-absent" => Ok(Column::FailedAbsent),
+            "decisive-absent" => Ok(Column::DecisiveAbsent),
+            "created" => Ok(Column::Created),
+            "success" => Ok(Column::Success),
+            "own-votes" => Ok(Column::OwnVotes),
+            "open-ballots" => Ok(Column::OpenBallots),
+            _ => Err(ErrorKind::UnknownColumn(s.to_string()).into()),
+        }
+    }
+}
+
+/// The narrowest that the name column is shrunk to, to fit the table into the terminal.
+const MIN_NAME_WIDTH: usize = 12;
+
+/// The text of each cell of the validator table, and the width of each column.
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    widths: Vec<usize>,
+}
+
+impl Table {
+    /// Creates a table with the given columns, and with the rows returned by `cells`. If
+    /// `max_width` is given and the table is wider, full keys are abbreviated and the name column
+    /// is truncated.
+    pub fn new<F>(columns: &[Column], cells: F, max_width: Option<usize>) -> Table
+    where
+        F: Fn(&[Column]) -> Vec<Vec<String>>,
+    {
+        let mut table = Table::with_columns(columns.to_vec(), &cells);
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => return table,
+        };
+        if table.total_width() > max_width {
+            let abbreviated = columns.iter().map(|c| c.abbreviated()).collect();
+            table = Table::with_columns(abbreviated, &cells);
+        }
+        let excess = table.total_width().saturating_sub(max_width);
+        if let Some(i) = table.columns.iter().position(|c| *c == Column::Name) {
+            let min_width = table.widths[i].min(MIN_NAME_WIDTH);
+            table.widths[i] = table.widths[i].saturating_sub(excess).max(min_width);
+        }
+        table
+    }
+
+    fn with_columns<F>(columns: Vec<Column>, cells: &F) -> Table
+    where
+        F: Fn(&[Column]) -> Vec<Vec<String>>,
+    {
+        let rows = cells(&columns);
+        let mut widths: Vec<usize> = columns.iter().map(|c| c.title().chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        Table {
+            columns,
+            rows,
+            widths,
+        }
+    }
+
+    /// Returns the width of a line, including the spaces between columns.
+    fn total_width(&self) -> usize {
+        self.widths.iter().sum::<usize>() + 2 * self.widths.len().saturating_sub(1)
+    }
+
+    /// Returns the header line with the column titles.
+    pub fn header(&self) -> String {
+        let titles: Vec<&str> = self.columns.iter().map(|c| c.title()).collect();
+        self.format_row(&titles)
+    }
+
+    /// Returns the line with the given index.
+    pub fn line(&self, i: usize) -> String {
+        self.format_row(&self.rows[i])
+    }
+
+    /// Returns the cells joined into a line, padded or truncated to the column widths. The last
+    /// column is not padded.
+    fn format_row<S: AsRef<str>>(&self, cells: &[S]) -> String {
+        let last = self.columns.len().saturating_sub(1);
+        let padded: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&self.widths)
+            .zip(cells)
+            .enumerate()
+            .map(|(i, ((column, &width), cell))| {
+                let cell = truncate(cell.as_ref(), width);
+                if column.is_numeric() {
+                    format!("{:>1$}", cell, width)
+                } else if i == last {
+                    cell
+                } else {
+                    format!("{:1$}", cell, width)
+                }
+            })
+            .collect();
+        padded.join("  ")
+    }
+}
+
+/// Returns the text, shortened to `width` characters with an ellipsis if it is longer.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
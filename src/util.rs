@@ -1,9 +1,10 @@
 use crate::contracts::v1::voting::logs::BallotCreated as BallotCreatedV1;
 use crate::contracts::v2::voting::logs::BallotCreated;
+use chrono::{TimeZone, Utc};
 use colored::{Color, Colorize};
 use ethabi::{self, Address, Bytes, FunctionOutputDecoder};
-use std::{fmt, u8};
-use web3;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 use web3::futures::Future;
 use web3::helpers::CallFuture;
 
@@ -176,3 +177,33 @@ impl IntoBallot for BallotCreatedV1 {
         }
     }
 }
+
+/// Converts a `bytes32` value to a string, ignoring the NUL padding at the end.
+pub fn bytes32_to_string(bytes: &ethabi::Hash) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Returns the current time as a Unix timestamp.
+pub fn now_timestamp() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    now.expect("current timestamp is earlier than the Unix epoch")
+        .as_secs()
+}
+
+/// Formats a Unix timestamp as a date, or `-` if it is zero.
+pub fn format_date(timestamp: u64) -> String {
+    match Utc.timestamp_opt(timestamp as i64, 0).single() {
+        Some(time) if timestamp != 0 => time.format("%Y-%m-%d").to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+/// Formats a Unix timestamp as a date and time in UTC, or `-` if it is zero.
+pub fn format_time(timestamp: u64) -> String {
+    match Utc.timestamp_opt(timestamp as i64, 0).single() {
+        Some(time) if timestamp != 0 => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+        _ => "-".to_string(),
+    }
+}
@@ -1,20 +1,82 @@
-use ethabi;
+use crate::util;
+use serde_derive::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const ERR_EPOCH: &str = "current timestamp is earlier than the Unix epoch";
+
+/// The criteria for warnings about licenses and metadata.
+#[derive(Clone, Copy, Debug)]
+pub struct MetadataChecks {
+    /// Warn if the license expires within this time.
+    pub expiry_window: Duration,
+    /// Warn if the metadata hasn't been updated for this long.
+    pub stale_after: Duration,
+}
+
+impl Default for MetadataChecks {
+    fn default() -> MetadataChecks {
+        MetadataChecks {
+            expiry_window: Duration::from_secs(30 * 24 * 60 * 60),
+            stale_after: Duration::from_secs(365 * 24 * 60 * 60),
+        }
+    }
+}
 
 /// Validator metadata.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Validator {
     pub first_name: String,
     pub last_name: String,
-    // bytes32 licenseId,
-    // string fullAddress,
-    // bytes32 state,
-    // uint256 zipcode,
-    // uint256 expirationDate,
-    // uint256 createdDate,
-    // uint256 updatedDate,
-    // uint256 minThreshold,
+    /// The notary license ID.
+    pub license_id: String,
+    pub full_address: String,
+    pub state: String,
+    pub zip_code: String,
+    /// The license expiration date, as a Unix timestamp.
+    pub expiration_date: u64,
+    /// The time the metadata was created, as a Unix timestamp.
+    pub created_date: u64,
+    /// The time the metadata was last changed, as a Unix timestamp.
+    pub updated_date: u64,
+    /// The number of confirmations required to change the metadata.
+    pub min_threshold: u64,
 }
 
+impl Validator {
+    /// Returns the validator's first and last name.
+    pub fn name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+
+    /// Returns warnings if the license has expired or is about to, or if the metadata is outdated.
+    pub fn warnings(&self, now: SystemTime, checks: &MetadataChecks) -> Vec<String> {
+        let now = now.duration_since(UNIX_EPOCH).expect(ERR_EPOCH).as_secs();
+        let mut warnings = Vec::new();
+        let expires = util::format_date(self.expiration_date);
+        if self.expiration_date == 0 {
+            warnings.push("License expiration date is not set".to_string());
+        } else if self.expiration_date <= now {
+            warnings.push(format!("License expired on {}", expires));
+        } else if self.expiration_date <= now.saturating_add(checks.expiry_window.as_secs()) {
+            warnings.push(format!("License expires on {}", expires));
+        }
+        let updated = if self.updated_date == 0 {
+            self.created_date
+        } else {
+            self.updated_date
+        };
+        if updated == 0 {
+            warnings.push("Metadata has no creation or update date".to_string());
+        } else if updated.saturating_add(checks.stale_after.as_secs()) <= now {
+            let date = util::format_date(updated);
+            warnings.push(format!("Metadata not updated since {}", date));
+        }
+        warnings
+    }
+}
+
+/// The output of `ValidatorMetadata.validators`.
 type ValidatorTuple = (
     ethabi::Hash,
     ethabi::Hash,
@@ -29,14 +91,59 @@ type ValidatorTuple = (
 );
 
 impl From<ValidatorTuple> for Validator {
-    fn from((first_name_h, last_name_h, ..): ValidatorTuple) -> Validator {
+    fn from(
+        (
+            first_name,
+            last_name,
+            license_id,
+            full_address,
+            state,
+            zip_code,
+            expiration_date,
+            created_date,
+            updated_date,
+            min_threshold,
+        ): ValidatorTuple,
+    ) -> Validator {
         Validator {
-            first_name: String::from_utf8_lossy(&*first_name_h)
-                .to_owned()
-                .to_string(),
-            last_name: String::from_utf8_lossy(&*last_name_h)
-                .to_owned()
-                .to_string(),
+            first_name: util::bytes32_to_string(&first_name),
+            last_name: util::bytes32_to_string(&last_name),
+            license_id: util::bytes32_to_string(&license_id),
+            full_address,
+            state: util::bytes32_to_string(&state),
+            zip_code: util::bytes32_to_string(&zip_code),
+            expiration_date: expiration_date.low_u64(),
+            created_date: created_date.low_u64(),
+            updated_date: updated_date.low_u64(),
+            min_threshold: min_threshold.low_u64(),
         }
     }
 }
+
+/// Displays all metadata fields, one per line, indented.
+impl Display for Validator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "  License ID:     {}", self.license_id)?;
+        writeln!(
+            f,
+            "  Address:        {}, {} {}",
+            self.full_address, self.state, self.zip_code
+        )?;
+        writeln!(
+            f,
+            "  Expires:        {}",
+            util::format_date(self.expiration_date)
+        )?;
+        writeln!(
+            f,
+            "  Created:        {}",
+            util::format_date(self.created_date)
+        )?;
+        writeln!(
+            f,
+            "  Updated:        {}",
+            util::format_date(self.updated_date)
+        )?;
+        writeln!(f, "  Min. threshold: {}", self.min_threshold)
+    }
+}